@@ -0,0 +1,171 @@
+use std::{iter, mem, time::Duration};
+
+use crate::game::{Input, GRID_SIZE};
+
+/// How held keys repeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Handling {
+    /// Delayed auto shift, how long left or right has to be held before it starts repeating.
+    pub das: Duration,
+    /// Auto repeat rate, the time between moves once it has. Zero slides straight to the wall.
+    pub arr: Duration,
+    /// The time between rows while soft drop is held.
+    pub soft_drop: Duration,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Self {
+            das: Duration::from_millis(167),
+            arr: Duration::from_millis(33),
+            soft_drop: Duration::from_millis(50),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Held {
+    input: Input,
+    time: Duration,
+    repeats: usize,
+}
+
+/// Turns presses and releases into the inputs the game should see. Every press goes through
+/// once, and sideways moves and soft drop keep repeating for as long as they're held.
+#[derive(Debug, Clone, Default)]
+pub struct Controls {
+    handling: Handling,
+    // in the order they were pressed
+    held: Vec<Held>,
+    pressed: Vec<Input>,
+}
+
+impl Controls {
+    pub fn new(handling: Handling) -> Self {
+        Self {
+            handling,
+            ..Default::default()
+        }
+    }
+
+    pub fn handling(&self) -> Handling {
+        self.handling
+    }
+
+    /// A key going down. Pressing something that's already held does nothing, so a terminal's
+    /// own key repeat can't double up with ours.
+    pub fn press(&mut self, input: Input) {
+        if self.is_held(input) {
+            return;
+        }
+        self.pressed.push(input);
+        self.held.push(Held {
+            input,
+            time: Duration::ZERO,
+            repeats: 0,
+        });
+    }
+
+    pub fn release(&mut self, input: Input) {
+        self.held.retain(|held| held.input != input);
+    }
+
+    pub fn is_held(&self, input: Input) -> bool {
+        self.held.iter().any(|held| held.input == input)
+    }
+
+    /// Lets `elapsed` go by, returning everything pressed since the last update followed by any
+    /// repeats that came due.
+    pub fn update(&mut self, elapsed: Duration) -> Vec<Input> {
+        let mut inputs = mem::take(&mut self.pressed);
+        // with both sides held the one pressed last wins
+        let shift = self
+            .held
+            .iter()
+            .rev()
+            .map(|held| held.input)
+            .find(|input| matches!(input, Input::Left | Input::Right));
+        let Handling {
+            das,
+            arr,
+            soft_drop,
+        } = self.handling;
+
+        for held in &mut self.held {
+            held.time += elapsed;
+            // a rate of zero goes as far as the board allows, straight across or straight down
+            let (delay, rate, instant) = match held.input {
+                Input::Left | Input::Right => (das, arr, GRID_SIZE.0),
+                Input::Down => (soft_drop, soft_drop, GRID_SIZE.1),
+                _ => continue,
+            };
+            if held.time < delay {
+                continue;
+            }
+            let due = if rate.is_zero() {
+                held.repeats + instant
+            } else {
+                1 + ((held.time - delay).as_nanos() / rate.as_nanos()) as usize
+            };
+            // the side that lost out stays charged but doesn't save its moves up
+            if held.input == Input::Down || Some(held.input) == shift {
+                inputs.extend(iter::repeat_n(held.input, due - held.repeats));
+            }
+            held.repeats = due;
+        }
+        inputs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        controls::{Controls, Handling},
+        game::{Input, GRID_SIZE},
+    };
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn shifts_wait_then_repeat() {
+        let mut controls = Controls::new(Handling {
+            das: ms(100),
+            arr: ms(20),
+            soft_drop: ms(50),
+        });
+        controls.press(Input::Left);
+        controls.press(Input::RotateRight);
+        assert_eq!(controls.update(ms(0)), [Input::Left, Input::RotateRight]);
+        assert!(controls.update(ms(99)).is_empty());
+        assert_eq!(controls.update(ms(1)), [Input::Left]);
+        assert_eq!(controls.update(ms(45)), [Input::Left, Input::Left]);
+
+        // the terminal repeating the key doesn't count as another press
+        controls.press(Input::Left);
+        assert!(controls.update(ms(0)).is_empty());
+
+        // right takes over while both are held, and left picks up again once it's let go
+        controls.press(Input::Right);
+        assert_eq!(controls.update(ms(50)), [Input::Right]);
+        controls.release(Input::Right);
+        assert_eq!(controls.update(ms(20)), [Input::Left]);
+        controls.release(Input::Left);
+        assert!(controls.update(ms(500)).is_empty());
+    }
+
+    #[test]
+    fn zero_arr_goes_straight_to_the_wall() {
+        let mut controls = Controls::new(Handling {
+            arr: Duration::ZERO,
+            ..Handling::default()
+        });
+        controls.press(Input::Right);
+        assert_eq!(controls.update(ms(0)).len(), 1);
+        let slide = controls.update(controls.handling().das);
+        assert_eq!(slide, [Input::Right; GRID_SIZE.0]);
+    }
+}
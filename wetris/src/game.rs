@@ -0,0 +1,701 @@
+use std::{fmt, mem, str::FromStr, time::Duration};
+
+use crate::{
+    piece::{Direction, Generator, Tetrimino},
+    scoring::{Clear, Scorer, Spin},
+};
+
+pub const GRID_SIZE: (usize, usize) = (10usize, 20usize);
+// most upcoming pieces a game can show
+pub const MAX_PREVIEW: usize = 5;
+/// Marks where the active piece would land in [`Game::view`], never a piece's own value.
+pub const GHOST: usize = 9;
+/// Rows an opponent sent over in versus mode.
+pub const GARBAGE: usize = 8;
+
+/// Locked blocks, `grid[y][x]`, 0 is empty.
+pub type Grid = [[usize; GRID_SIZE.0]; GRID_SIZE.1];
+
+const LINES_PER_LEVEL: usize = 10;
+// milliseconds a piece takes to fall a row from level 1 up, the last one holds after that
+const GRAVITY_MS: [u64; 15] = [
+    1000, 793, 618, 473, 355, 262, 190, 135, 94, 64, 43, 28, 18, 11, 7,
+];
+// how long a piece can rest on the stack before it locks
+const LOCK_DELAY: Duration = Duration::from_millis(500);
+// moves and rotations that can restart the lock delay before the piece drops any lower
+const LOCK_RESETS: usize = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Left,
+    Right,
+    // soft drop, one row at a time
+    Down,
+    // clockwise
+    RotateRight,
+    // counter-clockwise
+    RotateLeft,
+    // straight to the bottom and lock there
+    HardDrop,
+    // swap with the held piece, once per drop
+    Hold,
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Input::Left),
+            "right" => Ok(Input::Right),
+            "down" | "soft-drop" => Ok(Input::Down),
+            "rotate-right" => Ok(Input::RotateRight),
+            "rotate-left" => Ok(Input::RotateLeft),
+            "hard-drop" => Ok(Input::HardDrop),
+            "hold" => Ok(Input::Hold),
+            _ => Err(format!(
+                "unknown action '{s}', expected left, right, down, rotate-right, rotate-left, \
+                 hard-drop or hold"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Left => write!(f, "left"),
+            Input::Right => write!(f, "right"),
+            Input::Down => write!(f, "down"),
+            Input::RotateRight => write!(f, "rotate-right"),
+            Input::RotateLeft => write!(f, "rotate-left"),
+            Input::HardDrop => write!(f, "hard-drop"),
+            Input::Hold => write!(f, "hold"),
+        }
+    }
+}
+
+fn get_starting_position() -> (isize, isize) {
+    (3, 0)
+}
+
+/// How long a piece takes to fall one row at `level`.
+pub fn gravity(level: usize) -> Duration {
+    let row = level.clamp(1, GRAVITY_MS.len()) - 1;
+    Duration::from_millis(GRAVITY_MS[row])
+}
+
+/// A game of wetris with no terminal attached. The front end feeds it inputs with
+/// [`Game::step`] and tells it how much time has passed with [`Game::tick`], as often as it
+/// likes.
+pub struct Game {
+    grid: Grid,
+    generator: Generator,
+    // upcoming pieces, always MAX_PREVIEW long however many are shown
+    queue: Vec<Tetrimino>,
+    preview: usize,
+    piece: Tetrimino,
+    position: (isize, isize),
+    hold: Option<Tetrimino>,
+    // hold was used since the last piece locked
+    held: bool,
+    score: usize,
+    scorer: Scorer,
+    last_clear: Option<Clear>,
+    lines: usize,
+    // time towards the piece falling another row
+    fall: Duration,
+    // time the piece has spent resting on the stack
+    lock_timer: Duration,
+    resets: usize,
+    // the lowest row the piece has been on, dropping below it gives back the lock resets
+    lowest: isize,
+    // which kick the last rotation used, if the piece hasn't moved since
+    kick: Option<usize>,
+    // lines sent by an opponent and the column left open in them, they come in on the next lock
+    garbage: Vec<(usize, usize)>,
+    over: bool,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self::with_generator(Generator::default())
+    }
+
+    /// A game dealt pieces by `generator`, seed it for a game that can be played again.
+    pub fn with_generator(mut generator: Generator) -> Self {
+        let piece = generator.next().expect("the generator never runs out");
+        Self {
+            grid: [[0; GRID_SIZE.0]; GRID_SIZE.1],
+            queue: generator.by_ref().take(MAX_PREVIEW).collect(),
+            generator,
+            preview: 3,
+            piece,
+            position: get_starting_position(),
+            hold: None,
+            held: false,
+            score: 0,
+            scorer: Scorer::default(),
+            last_clear: None,
+            lines: 0,
+            fall: Duration::ZERO,
+            lock_timer: Duration::ZERO,
+            resets: 0,
+            lowest: get_starting_position().1,
+            kick: None,
+            garbage: vec![],
+            over: false,
+        }
+    }
+
+    /// How many upcoming pieces [`Game::next_pieces`] shows, from 1 to 5.
+    pub fn preview(mut self, preview: usize) -> Self {
+        self.preview = preview.clamp(1, MAX_PREVIEW);
+        self
+    }
+
+    /// Moves or rotates the active piece, returning whether there was room to. Rotations kick
+    /// the piece off walls and the stack when they can.
+    pub fn step(&mut self, input: Input) -> bool {
+        if self.over {
+            return false;
+        }
+        let (x, y) = self.position;
+        match input {
+            Input::Left => self.try_move((x - 1, y)),
+            Input::Right => self.try_move((x + 1, y)),
+            Input::Down => {
+                let moved = self.try_move((x, y + 1));
+                if moved {
+                    self.score += 1;
+                }
+                moved
+            }
+            Input::RotateRight => self.rotate(self.piece.rotate_right()),
+            Input::RotateLeft => self.rotate(self.piece.rotate_left()),
+            Input::HardDrop => {
+                let ghost = self.ghost();
+                if ghost != self.position {
+                    self.score += 2 * (ghost.1 - y) as usize;
+                    self.position = ghost;
+                    self.kick = None;
+                }
+                self.lock();
+                true
+            }
+            Input::Hold => self.hold(),
+        }
+    }
+
+    fn try_move(&mut self, position: (isize, isize)) -> bool {
+        if !self.fits(&self.piece, position) {
+            return false;
+        }
+        self.position = position;
+        self.kick = None;
+        self.moved();
+        true
+    }
+
+    fn rotate(&mut self, rotated: Tetrimino) -> bool {
+        let (x, y) = self.position;
+        let kicks = self.piece.kicks(rotated.direction());
+        let Some(kick) = kicks
+            .iter()
+            .position(|(dx, dy)| self.fits(&rotated, (x + dx, y + dy)))
+        else {
+            return false;
+        };
+        let (dx, dy) = kicks[kick];
+        self.piece = rotated;
+        self.position = (x + dx, y + dy);
+        self.kick = Some(kick);
+        self.moved();
+        true
+    }
+
+    // moving gives the piece more time before it locks, only so many times on the same row
+    fn moved(&mut self) {
+        if self.position.1 > self.lowest {
+            self.lowest = self.position.1;
+            self.resets = 0;
+            self.lock_timer = Duration::ZERO;
+        } else if self.resets < LOCK_RESETS {
+            self.resets += 1;
+            self.lock_timer = Duration::ZERO;
+        }
+    }
+
+    fn grounded(&self) -> bool {
+        let (x, y) = self.position;
+        !self.fits(&self.piece, (x, y + 1))
+    }
+
+    /// Lets `elapsed` go by. The piece falls as many rows as the level's gravity says it would
+    /// have in that time, and locks once it's rested on the stack for long enough.
+    pub fn tick(&mut self, elapsed: Duration) {
+        if self.over {
+            return;
+        }
+        if !self.grounded() {
+            self.fall += elapsed;
+            let row = gravity(self.level());
+            while self.fall >= row && !self.grounded() {
+                self.fall -= row;
+                let (x, y) = self.position;
+                self.position = (x, y + 1);
+                self.kick = None;
+                self.moved();
+            }
+            if !self.grounded() {
+                return;
+            }
+            // whatever time was left over after landing counts towards locking
+            self.lock_timer += self.fall.min(row);
+            self.fall = Duration::ZERO;
+        } else {
+            self.lock_timer += elapsed;
+        }
+        if self.lock_timer >= LOCK_DELAY {
+            self.lock();
+        }
+    }
+
+    fn hold(&mut self) -> bool {
+        if self.held {
+            return false;
+        }
+        let piece = self.piece.facing(Direction::Up);
+        self.piece = match self.hold.replace(piece) {
+            Some(held) => held,
+            None => self.next_piece(),
+        };
+        self.held = true;
+        self.spawn();
+        true
+    }
+
+    /// Where the active piece would land if it dropped straight down.
+    pub fn ghost(&self) -> (isize, isize) {
+        let (x, mut y) = self.position;
+        while self.fits(&self.piece, (x, y + 1)) {
+            y += 1;
+        }
+        (x, y)
+    }
+
+    fn fits(&self, piece: &Tetrimino, position: (isize, isize)) -> bool {
+        piece.cells(position).all(|(x, y, _)| {
+            (0..GRID_SIZE.0 as isize).contains(&x)
+                && (0..GRID_SIZE.1 as isize).contains(&y)
+                && self.grid[y as usize][x as usize] == 0
+        })
+    }
+
+    // a T that turned into place with three of the four corners around its middle filled. It
+    // only counts in full if both corners it points at are filled, or it took the last kick
+    fn spin(&self) -> Spin {
+        let (Tetrimino::T(direction), Some(kick)) = (self.piece, self.kick) else {
+            return Spin::None;
+        };
+        let (x, y) = self.position;
+        let filled = |(dx, dy): (isize, isize)| {
+            let (cx, cy) = (x + dx, y + dy);
+            !(0..GRID_SIZE.0 as isize).contains(&cx)
+                || !(0..GRID_SIZE.1 as isize).contains(&cy)
+                || self.grid[cy as usize][cx as usize] != 0
+        };
+        let corners = [(0, 0), (2, 0), (2, 2), (0, 2)];
+        if corners.into_iter().filter(|c| filled(*c)).count() < 3 {
+            return Spin::None;
+        }
+        let front = match direction {
+            Direction::Up => [(0, 0), (2, 0)],
+            Direction::Right => [(2, 0), (2, 2)],
+            Direction::Down => [(0, 2), (2, 2)],
+            Direction::Left => [(0, 0), (0, 2)],
+        };
+        if front.into_iter().all(filled) || kick == 4 {
+            Spin::Full
+        } else {
+            Spin::Mini
+        }
+    }
+
+    // writes the piece into the grid, clears any full lines and brings in the next piece
+    fn lock(&mut self) {
+        let spin = self.spin();
+        for (x, y, value) in self.piece.cells(self.position) {
+            self.grid[y as usize][x as usize] = value;
+        }
+        let cleared = self.clear_lines();
+        let clear = self.scorer.lock(cleared, spin, self.level());
+        self.score += clear.points;
+        self.lines += cleared;
+        if cleared > 0 || spin != Spin::None {
+            self.last_clear = Some(clear);
+        }
+        for (lines, hole) in mem::take(&mut self.garbage) {
+            self.raise(lines, hole);
+        }
+        if self.over {
+            return;
+        }
+
+        self.piece = self.next_piece();
+        self.held = false;
+        self.spawn();
+    }
+
+    /// Lines of garbage to push up from the bottom once the active piece locks, full apart from
+    /// the `hole` column.
+    pub fn add_garbage(&mut self, lines: usize, hole: usize) {
+        if lines > 0 {
+            self.garbage.push((lines, hole.min(GRID_SIZE.0 - 1)));
+        }
+    }
+
+    /// Garbage waiting to come in.
+    pub fn pending_garbage(&self) -> usize {
+        self.garbage.iter().map(|(lines, _)| lines).sum()
+    }
+
+    // pushing blocks out of the top of the grid ends the game
+    fn raise(&mut self, lines: usize, hole: usize) {
+        let lines = lines.min(GRID_SIZE.1);
+        if self.grid[..lines].iter().flatten().any(|cell| *cell != 0) {
+            self.over = true;
+        }
+        self.grid.rotate_left(lines);
+        for row in &mut self.grid[GRID_SIZE.1 - lines..] {
+            *row = [GARBAGE; GRID_SIZE.0];
+            row[hole] = 0;
+        }
+    }
+
+    // puts the active piece back at the top, the game is over if there's no room for it
+    fn spawn(&mut self) {
+        self.position = get_starting_position();
+        self.fall = Duration::ZERO;
+        self.lock_timer = Duration::ZERO;
+        self.resets = 0;
+        self.lowest = self.position.1;
+        self.kick = None;
+        if !self.fits(&self.piece, self.position) {
+            self.over = true;
+        }
+    }
+
+    fn next_piece(&mut self) -> Tetrimino {
+        let next = self.generator.next().expect("the generator never runs out");
+        self.queue.push(next);
+        self.queue.remove(0)
+    }
+
+    fn clear_lines(&mut self) -> usize {
+        let kept: Vec<[usize; GRID_SIZE.0]> = self
+            .grid
+            .iter()
+            .filter(|row| row.contains(&0))
+            .copied()
+            .collect();
+        let cleared = GRID_SIZE.1 - kept.len();
+        let mut grid = [[0; GRID_SIZE.0]; GRID_SIZE.1];
+        grid[cleared..].copy_from_slice(&kept);
+        self.grid = grid;
+        cleared
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// The grid with the active piece drawn in, and [`GHOST`] where it would land.
+    pub fn view(&self) -> Grid {
+        let mut grid = self.grid;
+        let ghost = self
+            .piece
+            .cells(self.ghost())
+            .map(|(x, y, _)| (x, y, GHOST));
+        for (x, y, value) in ghost.chain(self.piece.cells(self.position)) {
+            if let Some(cell) = grid
+                .get_mut(y as usize)
+                .and_then(|row| row.get_mut(x as usize))
+            {
+                *cell = value;
+            }
+        }
+        grid
+    }
+
+    /// The pieces coming up next, soonest first.
+    pub fn next_pieces(&self) -> &[Tetrimino] {
+        &self.queue[..self.preview]
+    }
+
+    pub fn held(&self) -> Option<Tetrimino> {
+        self.hold
+    }
+
+    pub fn piece(&self) -> Tetrimino {
+        self.piece
+    }
+
+    pub fn position(&self) -> (isize, isize) {
+        self.position
+    }
+
+    pub fn score(&self) -> usize {
+        self.score
+    }
+
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// Starts at 1 and goes up every ten lines.
+    pub fn level(&self) -> usize {
+        1 + self.lines / LINES_PER_LEVEL
+    }
+
+    /// The last time a piece cleared lines or was spun into place.
+    pub fn last_clear(&self) -> Option<Clear> {
+        self.last_clear
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        game::{gravity, Game, Input, GHOST, GRID_SIZE, LOCK_RESETS},
+        piece::{Direction, Generator, Randomizer, Tetrimino},
+        scoring::Spin,
+    };
+
+    fn game(piece: Tetrimino) -> Game {
+        Game {
+            piece,
+            ..Game::new()
+        }
+    }
+
+    fn placed(piece: Tetrimino, position: (isize, isize)) -> Game {
+        Game {
+            position,
+            ..game(piece)
+        }
+    }
+
+    // ticks until the active piece locks
+    fn drop(game: &mut Game) {
+        let grid = *game.grid();
+        while *game.grid() == grid && !game.is_over() {
+            game.tick(Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn walls_stop_the_piece() {
+        let mut game = game(Tetrimino::Square(Direction::Up));
+        while game.step(Input::Left) {}
+        // the square sits one column into its 4x4 box
+        assert_eq!(game.position(), (-1, 0));
+        while game.step(Input::Right) {}
+        assert_eq!(game.position(), (GRID_SIZE.0 as isize - 3, 0));
+        while game.step(Input::Down) {}
+        assert_eq!(game.position().1, GRID_SIZE.1 as isize - 2);
+        assert_eq!(game.score(), GRID_SIZE.1 - 2);
+    }
+
+    #[test]
+    fn kicks_off_walls() {
+        // an upright straight piece against the left wall has to move right to lie flat
+        let mut game = placed(Tetrimino::Straight(Direction::Left), (-1, 5));
+        assert!(game.step(Input::RotateRight));
+        assert_eq!(game.piece(), Tetrimino::Straight(Direction::Up));
+        assert_eq!(game.position(), (0, 5));
+
+        // and a T against the right wall moves left
+        let mut game = placed(Tetrimino::T(Direction::Left), (GRID_SIZE.0 as isize - 2, 5));
+        assert!(game.step(Input::RotateRight));
+        assert_eq!(game.position(), (GRID_SIZE.0 as isize - 3, 5));
+
+        // a square never moves when it turns
+        let mut game = placed(Tetrimino::Square(Direction::Up), (-1, 5));
+        assert!(game.step(Input::RotateLeft));
+        assert_eq!(game.position(), (-1, 5));
+    }
+
+    #[test]
+    fn kicks_off_the_stack() {
+        let mut game = placed(Tetrimino::T(Direction::Up), (4, 10));
+        // blocks the bottom of the T turned clockwise where it is, the second kick is one left
+        game.grid[12][5] = 1;
+        assert!(game.step(Input::RotateRight));
+        assert_eq!(game.piece(), Tetrimino::T(Direction::Right));
+        assert_eq!(game.position(), (3, 10));
+
+        // walled in on both sides, so there's nowhere to turn back to
+        let mut game = placed(Tetrimino::Straight(Direction::Right), (0, 16));
+        for row in &mut game.grid[14..] {
+            *row = [1, 1, 0, 1, 1, 1, 1, 1, 1, 1];
+        }
+        assert!(!game.step(Input::RotateLeft));
+        assert!(!game.step(Input::RotateRight));
+        assert_eq!(game.piece(), Tetrimino::Straight(Direction::Right));
+    }
+
+    #[test]
+    fn hold_once_per_drop() {
+        let mut game = Game::with_generator(Generator::new(Randomizer::Bag, Some(3))).preview(2);
+        let (first, next) = (game.piece(), game.next_pieces().to_vec());
+        assert_eq!(next.len(), 2);
+
+        game.step(Input::RotateRight);
+        assert!(game.step(Input::Hold));
+        assert_eq!(game.held(), Some(first.facing(Direction::Up)));
+        assert_eq!(game.piece(), next[0]);
+        assert!(!game.step(Input::Hold));
+
+        // once the piece locks the held one can come back
+        game.step(Input::HardDrop);
+        assert_eq!(game.piece(), next[1]);
+        assert!(game.step(Input::Hold));
+        assert_eq!(game.piece(), first);
+        assert_eq!(game.held(), Some(next[1]));
+    }
+
+    #[test]
+    fn hard_drop_lands_on_the_ghost() {
+        let mut game = game(Tetrimino::Square(Direction::Up));
+        game.grid[GRID_SIZE.1 - 1][5] = 1;
+        let ghost = game.ghost();
+        assert_eq!(ghost, (3, GRID_SIZE.1 as isize - 3));
+        assert_eq!(game.view()[GRID_SIZE.1 - 2][4], GHOST);
+
+        assert!(game.step(Input::HardDrop));
+        assert_eq!(game.score(), 2 * ghost.1 as usize);
+        assert_eq!(game.grid()[GRID_SIZE.1 - 2][4..6], [2, 2]);
+        assert_eq!(game.position(), (3, 0));
+    }
+
+    #[test]
+    fn full_lines_are_cleared() {
+        let mut game = game(Tetrimino::Straight(Direction::Up));
+        // the bottom row is full apart from the four cells the straight piece fills
+        game.grid[GRID_SIZE.1 - 1] = [1, 1, 1, 0, 0, 0, 0, 1, 1, 1];
+        game.grid[GRID_SIZE.1 - 2] = [1, 1, 0, 0, 0, 0, 0, 0, 0, 0];
+        while game.step(Input::Down) {}
+        let before = game.score();
+        drop(&mut game);
+
+        assert_eq!(game.lines(), 1);
+        assert_eq!(game.score(), before + 100);
+        assert_eq!(game.last_clear().unwrap().name(), "SINGLE");
+        assert_eq!(game.grid()[GRID_SIZE.1 - 1], [1, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(game.grid()[GRID_SIZE.1 - 2].iter().all(|c| *c == 0));
+    }
+
+    #[test]
+    fn gravity_speeds_up_every_ten_lines() {
+        assert_eq!(gravity(1), Duration::from_secs(1));
+        assert_eq!(gravity(20), gravity(15));
+
+        let mut game = game(Tetrimino::Square(Direction::Up));
+        game.tick(Duration::from_millis(2500));
+        assert_eq!(game.position().1, 2);
+        // the half second left over still counts
+        game.tick(Duration::from_millis(500));
+        assert_eq!(game.position().1, 3);
+
+        game.lines = 10;
+        assert_eq!(game.level(), 2);
+        game.tick(gravity(2));
+        assert_eq!(game.position().1, 4);
+    }
+
+    #[test]
+    fn moving_puts_off_locking_for_a_while() {
+        let mut game = game(Tetrimino::Square(Direction::Up));
+        while game.step(Input::Down) {}
+        let grid = *game.grid();
+        for i in 0..LOCK_RESETS {
+            game.tick(Duration::from_millis(400));
+            let side = if i % 2 == 0 {
+                Input::Left
+            } else {
+                Input::Right
+            };
+            assert!(game.step(side));
+        }
+        assert_eq!(*game.grid(), grid);
+
+        // out of resets, so this move doesn't buy any more time
+        game.tick(Duration::from_millis(400));
+        assert!(game.step(Input::Left));
+        assert_eq!(*game.grid(), grid);
+        game.tick(Duration::from_millis(100));
+        assert_ne!(*game.grid(), grid);
+    }
+
+    #[test]
+    fn t_spin_double() {
+        let mut game = placed(Tetrimino::T(Direction::Right), (3, 17));
+        game.grid[GRID_SIZE.1 - 1] = [1, 1, 1, 1, 0, 1, 1, 1, 1, 1];
+        game.grid[GRID_SIZE.1 - 2] = [1, 1, 1, 0, 0, 0, 1, 1, 1, 1];
+        // the overhang that makes it a spin rather than a drop
+        game.grid[GRID_SIZE.1 - 3][3] = 1;
+        assert!(game.step(Input::RotateRight));
+        assert!(game.step(Input::HardDrop));
+
+        let clear = game.last_clear().unwrap();
+        assert_eq!((clear.lines, clear.spin), (2, Spin::Full));
+        assert_eq!(game.score(), 1200);
+        assert_eq!(game.grid()[GRID_SIZE.1 - 1], [0, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn garbage_comes_in_on_the_next_lock() {
+        let mut game = game(Tetrimino::Square(Direction::Up));
+        game.add_garbage(2, 4);
+        game.add_garbage(1, 0);
+        assert_eq!(game.pending_garbage(), 3);
+        assert!(game.grid().iter().flatten().all(|c| *c == 0));
+
+        game.step(Input::HardDrop);
+        assert_eq!(game.pending_garbage(), 0);
+        let bottom = game.grid()[GRID_SIZE.1 - 1];
+        assert_eq!(bottom, [0, 8, 8, 8, 8, 8, 8, 8, 8, 8]);
+        assert_eq!(game.grid()[GRID_SIZE.1 - 2][4], 0);
+        // the square was pushed up along with everything else
+        assert_eq!(game.grid()[GRID_SIZE.1 - 5][4..6], [2, 2]);
+
+        game.add_garbage(GRID_SIZE.1, 0);
+        game.step(Input::HardDrop);
+        assert!(game.is_over());
+    }
+
+    #[test]
+    fn topping_out_ends_the_game() {
+        let mut game = game(Tetrimino::Square(Direction::Up));
+        for row in &mut game.grid[3..] {
+            *row = [1, 1, 1, 1, 1, 1, 1, 1, 1, 0];
+        }
+        drop(&mut game);
+        assert!(game.is_over());
+        assert!(!game.step(Input::Left));
+    }
+}
@@ -0,0 +1,539 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Tetrimino {
+    Straight(Direction), // vertical and horizontal reflection symmetry, and two-fold rotational symmetry
+    Square(Direction), // vertical and horizontal reflection symmetry, and four-fold rotational symmetry
+    T(Direction),      // vertical reflection symmetry only
+    L(Direction),      // no symmetry
+    J(Direction),      // no symmetry, the mirror image of L
+    S(Direction),      // two-fold rotational symmetry only
+    Z(Direction),      // two-fold rotational symmetry only
+}
+
+/// Rotation states from the Super Rotation System. Up is how a piece spawns (0), Right is a turn
+/// clockwise from there (R), Down is upside down (2) and Left is a turn counter-clockwise (L).
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn rotate_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+            Direction::Right => Direction::Down,
+        }
+    }
+
+    pub fn rotate_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Down,
+            Direction::Right => Direction::Up,
+        }
+    }
+}
+
+impl Tetrimino {
+    pub fn direction(&self) -> Direction {
+        match *self {
+            Self::Straight(direction)
+            | Self::Square(direction)
+            | Self::T(direction)
+            | Self::L(direction)
+            | Self::J(direction)
+            | Self::S(direction)
+            | Self::Z(direction) => direction,
+        }
+    }
+
+    /// The same piece facing `direction`.
+    pub fn facing(&self, direction: Direction) -> Self {
+        match *self {
+            Self::Straight(_) => Self::Straight(direction),
+            Self::Square(_) => Self::Square(direction),
+            Self::T(_) => Self::T(direction),
+            Self::L(_) => Self::L(direction),
+            Self::J(_) => Self::J(direction),
+            Self::S(_) => Self::S(direction),
+            Self::Z(_) => Self::Z(direction),
+        }
+    }
+
+    pub fn rotate_right(&self) -> Self {
+        self.facing(self.direction().rotate_right())
+    }
+
+    pub fn rotate_left(&self) -> Self {
+        self.facing(self.direction().rotate_left())
+    }
+
+    /// Where to try the piece, relative to where it is now, when it turns to face `to`. The
+    /// first offset that fits wins, and if none do the piece doesn't turn.
+    pub fn kicks(&self, to: Direction) -> Vec<(isize, isize)> {
+        let table = match self {
+            Self::Square(_) => return vec![(0, 0)],
+            Self::Straight(_) => &I_KICKS,
+            _ => &JLSTZ_KICKS,
+        };
+        let from = self.direction();
+        table
+            .iter()
+            .find(|(f, t, _)| *f == from && *t == to)
+            .map_or(vec![(0, 0)], |(_, _, offsets)| {
+                // the table has y going up, the grid has it going down
+                offsets.iter().map(|(x, y)| (*x, -*y)).collect()
+            })
+    }
+
+    /// The letter a piece usually goes by, I for the straight one and O for the square.
+    pub fn letter(&self) -> char {
+        match self {
+            Self::Straight(_) => 'I',
+            Self::Square(_) => 'O',
+            Self::T(_) => 'T',
+            Self::L(_) => 'L',
+            Self::J(_) => 'J',
+            Self::S(_) => 'S',
+            Self::Z(_) => 'Z',
+        }
+    }
+
+    /// What the piece leaves in the grid, the same number for every direction it faces.
+    pub fn value(&self) -> usize {
+        match self {
+            Self::Straight(_) => 1,
+            Self::Square(_) => 2,
+            Self::T(_) => 3,
+            Self::L(_) => 4,
+            Self::J(_) => 5,
+            Self::S(_) => 6,
+            Self::Z(_) => 7,
+        }
+    }
+
+    pub fn shape(&self) -> &'static [[usize; 4]; 4] {
+        SHAPES
+            .iter()
+            .find(|(mino, _)| mino == self)
+            .map(|(_, shape)| shape)
+            .expect("every tetrimino has a shape")
+    }
+
+    /// The filled cells of this piece with its top left corner at `pos`, as (x, y, value).
+    pub fn cells(&self, pos: (isize, isize)) -> impl Iterator<Item = (isize, isize, usize)> {
+        let shape = self.shape();
+        (0..4).flat_map(move |y| {
+            (0..4).filter_map(move |x| match shape[y][x] {
+                0 => None,
+                value => Some((pos.0 + x as isize, pos.1 + y as isize, value)),
+            })
+        })
+    }
+}
+
+pub const SHAPES: [(Tetrimino, [[usize; 4]; 4]); 28] = [
+    // Straight
+    (
+        Tetrimino::Straight(Direction::Up),
+        [
+            [0, 0, 0, 0], //
+            [1, 1, 1, 1], //
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::Straight(Direction::Right),
+        [
+            [0, 0, 1, 0], //
+            [0, 0, 1, 0], //
+            [0, 0, 1, 0], //
+            [0, 0, 1, 0], //
+        ],
+    ),
+    (
+        Tetrimino::Straight(Direction::Down),
+        [
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+            [1, 1, 1, 1], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::Straight(Direction::Left),
+        [
+            [0, 1, 0, 0], //
+            [0, 1, 0, 0], //
+            [0, 1, 0, 0], //
+            [0, 1, 0, 0], //
+        ],
+    ),
+    // Square
+    (
+        Tetrimino::Square(Direction::Up),
+        [
+            [0, 2, 2, 0], //
+            [0, 2, 2, 0], //
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::Square(Direction::Right),
+        [
+            [0, 2, 2, 0], //
+            [0, 2, 2, 0], //
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::Square(Direction::Down),
+        [
+            [0, 2, 2, 0], //
+            [0, 2, 2, 0], //
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::Square(Direction::Left),
+        [
+            [0, 2, 2, 0], //
+            [0, 2, 2, 0], //
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    // T
+    (
+        Tetrimino::T(Direction::Up),
+        [
+            [0, 3, 0, 0], //
+            [3, 3, 3, 0], //
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::T(Direction::Right),
+        [
+            [0, 3, 0, 0], //
+            [0, 3, 3, 0], //
+            [0, 3, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::T(Direction::Down),
+        [
+            [0, 0, 0, 0], //
+            [3, 3, 3, 0], //
+            [0, 3, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::T(Direction::Left),
+        [
+            [0, 3, 0, 0], //
+            [3, 3, 0, 0], //
+            [0, 3, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    // L
+    (
+        Tetrimino::L(Direction::Up),
+        [
+            [0, 0, 4, 0], //
+            [4, 4, 4, 0], //
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::L(Direction::Right),
+        [
+            [0, 4, 0, 0], //
+            [0, 4, 0, 0], //
+            [0, 4, 4, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::L(Direction::Down),
+        [
+            [0, 0, 0, 0], //
+            [4, 4, 4, 0], //
+            [4, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::L(Direction::Left),
+        [
+            [4, 4, 0, 0], //
+            [0, 4, 0, 0], //
+            [0, 4, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    // J
+    (
+        Tetrimino::J(Direction::Up),
+        [
+            [5, 0, 0, 0], //
+            [5, 5, 5, 0], //
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::J(Direction::Right),
+        [
+            [0, 5, 5, 0], //
+            [0, 5, 0, 0], //
+            [0, 5, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::J(Direction::Down),
+        [
+            [0, 0, 0, 0], //
+            [5, 5, 5, 0], //
+            [0, 0, 5, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::J(Direction::Left),
+        [
+            [0, 5, 0, 0], //
+            [0, 5, 0, 0], //
+            [5, 5, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    // S
+    (
+        Tetrimino::S(Direction::Up),
+        [
+            [0, 6, 6, 0], //
+            [6, 6, 0, 0], //
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::S(Direction::Right),
+        [
+            [0, 6, 0, 0], //
+            [0, 6, 6, 0], //
+            [0, 0, 6, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::S(Direction::Down),
+        [
+            [0, 0, 0, 0], //
+            [0, 6, 6, 0], //
+            [6, 6, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::S(Direction::Left),
+        [
+            [6, 0, 0, 0], //
+            [6, 6, 0, 0], //
+            [0, 6, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    // Z
+    (
+        Tetrimino::Z(Direction::Up),
+        [
+            [7, 7, 0, 0], //
+            [0, 7, 7, 0], //
+            [0, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::Z(Direction::Right),
+        [
+            [0, 0, 7, 0], //
+            [0, 7, 7, 0], //
+            [0, 7, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::Z(Direction::Down),
+        [
+            [0, 0, 0, 0], //
+            [7, 7, 0, 0], //
+            [0, 7, 7, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+    (
+        Tetrimino::Z(Direction::Left),
+        [
+            [0, 7, 0, 0], //
+            [7, 7, 0, 0], //
+            [7, 0, 0, 0], //
+            [0, 0, 0, 0], //
+        ],
+    ),
+];
+
+type Kicks = [(Direction, Direction, [(isize, isize); 5]); 8];
+
+// SRS wall kicks as (from, to, offsets), tried in order until one fits. The offsets are written
+// the way the guideline lists them, with y pointing up, so they're flipped before use.
+#[rustfmt::skip]
+const JLSTZ_KICKS: Kicks = [
+    (Direction::Up, Direction::Right, [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]),
+    (Direction::Right, Direction::Up, [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]),
+    (Direction::Right, Direction::Down, [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]),
+    (Direction::Down, Direction::Right, [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]),
+    (Direction::Down, Direction::Left, [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]),
+    (Direction::Left, Direction::Down, [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]),
+    (Direction::Left, Direction::Up, [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]),
+    (Direction::Up, Direction::Left, [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]),
+];
+
+#[rustfmt::skip]
+const I_KICKS: Kicks = [
+    (Direction::Up, Direction::Right, [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)]),
+    (Direction::Right, Direction::Up, [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)]),
+    (Direction::Right, Direction::Down, [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]),
+    (Direction::Down, Direction::Right, [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)]),
+    (Direction::Down, Direction::Left, [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)]),
+    (Direction::Left, Direction::Down, [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)]),
+    (Direction::Left, Direction::Up, [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)]),
+    (Direction::Up, Direction::Left, [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]),
+];
+
+pub const MINOS: [Tetrimino; 7] = [
+    Tetrimino::Straight(Direction::Up),
+    Tetrimino::Square(Direction::Up),
+    Tetrimino::L(Direction::Up),
+    Tetrimino::J(Direction::Up),
+    Tetrimino::T(Direction::Up),
+    Tetrimino::S(Direction::Up),
+    Tetrimino::Z(Direction::Up),
+];
+
+/// How the next piece is picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Randomizer {
+    // all seven pieces in a shuffled bag, a new bag once it's empty
+    #[default]
+    Bag,
+    // any piece, every time, so the same one can come up again and again
+    Uniform,
+}
+
+/// Deals out pieces. Two generators with the same seed deal the same pieces.
+pub struct Generator {
+    randomizer: Randomizer,
+    rng: StdRng,
+    bag: Vec<Tetrimino>,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new(Randomizer::default(), None)
+    }
+}
+
+impl Generator {
+    pub fn new(randomizer: Randomizer, seed: Option<u64>) -> Self {
+        Self {
+            randomizer,
+            rng: match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            bag: vec![],
+        }
+    }
+
+    pub fn randomizer(&self) -> Randomizer {
+        self.randomizer
+    }
+}
+
+impl Iterator for Generator {
+    type Item = Tetrimino;
+
+    fn next(&mut self) -> Option<Tetrimino> {
+        match self.randomizer {
+            Randomizer::Uniform => Some(MINOS[self.rng.gen::<usize>() % MINOS.len()]),
+            Randomizer::Bag => {
+                if self.bag.is_empty() {
+                    self.bag = MINOS.to_vec();
+                    self.bag.shuffle(&mut self.rng);
+                }
+                self.bag.pop()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::piece::{Direction, Generator, Randomizer, MINOS};
+
+    #[test]
+    fn four_turns_come_back_round() {
+        for mino in MINOS {
+            let mut turned = mino;
+            for _ in 0..4 {
+                assert_eq!(turned.cells((0, 0)).count(), 4);
+                assert!(turned.cells((0, 0)).all(|(_, _, v)| v == mino.value()));
+                assert_eq!(turned.rotate_left().rotate_right(), turned);
+                turned = turned.rotate_right();
+            }
+            assert_eq!(turned, mino);
+            assert_eq!(mino.direction(), Direction::Up);
+        }
+        let i = MINOS[0];
+        assert_eq!(i.kicks(Direction::Right)[1], (-2, 0));
+        assert_eq!(i.kicks(Direction::Right)[3], (-2, 1));
+    }
+
+    #[test]
+    fn every_bag_has_all_seven() {
+        let pieces: Vec<_> = Generator::new(Randomizer::Bag, Some(1)).take(70).collect();
+        for bag in pieces.chunks(MINOS.len()) {
+            assert_eq!(HashSet::<_>::from_iter(bag).len(), MINOS.len());
+        }
+    }
+
+    #[test]
+    fn seeds_repeat() {
+        for randomizer in [Randomizer::Bag, Randomizer::Uniform] {
+            let a: Vec<_> = Generator::new(randomizer, Some(42)).take(20).collect();
+            let b: Vec<_> = Generator::new(randomizer, Some(42)).take(20).collect();
+            assert_eq!(a, b);
+        }
+    }
+}
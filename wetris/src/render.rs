@@ -0,0 +1,345 @@
+use crossterm::style::Color;
+
+use crate::{
+    game::{Game, Grid, GHOST, MAX_PREVIEW},
+    piece::{Direction, Tetrimino},
+    GRID_SIZE,
+};
+
+// width of the panels either side of the board
+const PANEL: u16 = 12;
+// the board and its border
+const BOARD_HEIGHT: u16 = GRID_SIZE.1 as u16 + 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub ch: char,
+    pub colour: Color,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            colour: Color::Reset,
+        }
+    }
+}
+
+/// One frame's worth of characters, drawn off screen and then compared with the last frame so
+/// only what changed gets sent to the terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Canvas {
+    pub fn new((width, height): (u16, u16)) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    pub fn get(&self, x: u16, y: u16) -> Option<Cell> {
+        self.index(x, y).map(|i| self.cells[i])
+    }
+
+    /// Anything that falls off the edge is left out.
+    pub fn put(&mut self, x: u16, y: u16, ch: char, colour: Color) {
+        if let Some(i) = self.index(x, y) {
+            self.cells[i] = Cell { ch, colour };
+        }
+    }
+
+    pub fn text(&mut self, x: u16, y: u16, text: &str, colour: Color) {
+        for (i, ch) in text.chars().enumerate() {
+            self.put(x.saturating_add(i as u16), y, ch, colour);
+        }
+    }
+
+    /// The cells that differ from `before`, which has to be the same size.
+    pub fn changes<'a>(
+        &'a self,
+        before: &'a Canvas,
+    ) -> impl Iterator<Item = (u16, u16, Cell)> + 'a {
+        let width = self.width.max(1) as usize;
+        self.cells
+            .iter()
+            .zip(&before.cells)
+            .enumerate()
+            .filter(|(_, (now, then))| now != then)
+            .map(move |(i, (now, _))| ((i % width) as u16, (i / width) as u16, *now))
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y as usize * self.width as usize + x as usize)
+    }
+}
+
+/// The colour a grid value is drawn in, every piece has its own.
+pub fn colour(value: usize) -> Color {
+    match value {
+        1 => Color::Cyan,
+        2 => Color::Yellow,
+        3 => Color::Magenta,
+        4 => Color::DarkYellow,
+        5 => Color::Blue,
+        6 => Color::Green,
+        7 => Color::Red,
+        GHOST => Color::DarkGrey,
+        _ => Color::Grey,
+    }
+}
+
+// two characters a cell when there's room, so the blocks come out square. `boards` is how many
+// sit side by side
+fn cell_width(canvas: &Canvas, boards: u16) -> Option<u16> {
+    let (width, height) = canvas.size();
+    if height < BOARD_HEIGHT {
+        return None;
+    }
+    [2, 1]
+        .into_iter()
+        .find(|cell| layout_width(*cell, boards) <= width)
+}
+
+fn layout_width(cell: u16, boards: u16) -> u16 {
+    2 * (PANEL + 1) + boards * board_width(cell) + (boards - 1) * 2
+}
+
+fn board_width(cell: u16) -> u16 {
+    GRID_SIZE.0 as u16 * cell + 2
+}
+
+/// Lines of text in the middle of the canvas.
+pub fn draw_message(canvas: &mut Canvas, lines: &[&str]) {
+    let (width, height) = canvas.size();
+    let top = height.saturating_sub(lines.len() as u16) / 2;
+    for (i, line) in lines.iter().enumerate() {
+        let x = width.saturating_sub(line.chars().count() as u16) / 2;
+        canvas.text(x, top + i as u16, line, Color::Reset);
+    }
+}
+
+/// The board in the middle of the canvas, with the held piece and the score on its left and the
+/// pieces coming up on its right.
+pub fn draw_game(canvas: &mut Canvas, game: &Game) {
+    let Some(cell) = cell_width(canvas, 1) else {
+        canvas.text(0, 0, "make the terminal bigger", Color::Reset);
+        return;
+    };
+    let (width, height) = canvas.size();
+    let x = (width - layout_width(cell, 1)) / 2;
+    let y = (height - BOARD_HEIGHT) / 2;
+    draw_player(canvas, (x, y), cell, game);
+}
+
+/// What a versus client knows about the other player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opponent<'a> {
+    pub name: &'a str,
+    pub board: &'a Grid,
+    pub score: usize,
+    pub lines: usize,
+}
+
+/// Your own game as [`draw_game`] lays it out, with the opponent's board to its right and
+/// `message` over the top of both.
+pub fn draw_versus(canvas: &mut Canvas, game: &Game, opponent: Opponent, message: &[&str]) {
+    let Some(cell) = cell_width(canvas, 2) else {
+        canvas.text(0, 0, "make the terminal bigger", Color::Reset);
+        return;
+    };
+    let (width, height) = canvas.size();
+    let x = (width - layout_width(cell, 2)) / 2;
+    let y = (height - BOARD_HEIGHT) / 2;
+    draw_player(canvas, (x, y), cell, game);
+
+    let theirs = x + layout_width(cell, 1) + 2;
+    draw_board(canvas, (theirs, y), cell, opponent.board);
+    // over the border, top and bottom
+    let name: String = opponent
+        .name
+        .chars()
+        .take(board_width(cell) as usize - 4)
+        .collect();
+    canvas.text(theirs + 2, y, &format!(" {name} "), Color::Reset);
+    let score = format!(" {} / {} ", opponent.score, opponent.lines);
+    canvas.text(theirs + 2, y + BOARD_HEIGHT - 1, &score, Color::Reset);
+
+    if !message.is_empty() {
+        let mut banner = Canvas::new((width, message.len() as u16 + 2));
+        draw_message(&mut banner, message);
+        let top = (height - banner.height) / 2;
+        for row in 0..banner.height {
+            for column in 0..width {
+                let cell = banner.get(column, row).unwrap_or_default();
+                canvas.put(column, top + row, cell.ch, cell.colour);
+            }
+        }
+    }
+}
+
+fn draw_player(canvas: &mut Canvas, (x, y): (u16, u16), cell: u16, game: &Game) {
+    canvas.text(x, y + 1, "HOLD", Color::Reset);
+    if let Some(held) = game.held() {
+        draw_piece(canvas, (x, y + 3), cell, held);
+    }
+    let stats = [
+        ("SCORE", game.score()),
+        ("LEVEL", game.level()),
+        ("LINES", game.lines()),
+    ];
+    for (i, (name, value)) in stats.into_iter().enumerate() {
+        let row = y + 7 + 3 * i as u16;
+        canvas.text(x, row, name, Color::Reset);
+        canvas.text(x, row + 1, &value.to_string(), Color::Reset);
+    }
+    if game.pending_garbage() > 0 {
+        let garbage = format!("+{} GARBAGE", game.pending_garbage());
+        canvas.text(x, y + 15, &garbage, Color::Red);
+    }
+    if let Some(clear) = game.last_clear() {
+        let row = y + 17;
+        if clear.back_to_back {
+            canvas.text(x, row, "BACK-TO-BACK", Color::Reset);
+        }
+        // long names like MINI T-SPIN SINGLE go over two lines
+        let name = clear.name();
+        let (first, second) = match name.rsplit_once(' ') {
+            Some((first, second)) if name.len() > PANEL as usize => (first, second),
+            _ => (name.as_str(), ""),
+        };
+        canvas.text(x, row + 1, first, Color::Reset);
+        canvas.text(x, row + 2, second, Color::Reset);
+        if clear.combo > 0 {
+            canvas.text(x, row + 3, &format!("{} COMBO", clear.combo), Color::Reset);
+        }
+    }
+
+    let board = x + PANEL + 1;
+    draw_board(canvas, (board, y), cell, &game.view());
+
+    let next = board + board_width(cell) + 1;
+    canvas.text(next, y + 1, "NEXT", Color::Reset);
+    for (i, piece) in game.next_pieces().iter().take(MAX_PREVIEW).enumerate() {
+        draw_piece(canvas, (next, y + 3 + 3 * i as u16), cell, *piece);
+    }
+}
+
+/// A grid with a border round it, the top left corner of the border goes at `(x, y)`.
+pub fn draw_board(canvas: &mut Canvas, (x, y): (u16, u16), cell: u16, grid: &Grid) {
+    let inner = GRID_SIZE.0 as u16 * cell;
+    let (right, bottom) = (x + inner + 1, y + GRID_SIZE.1 as u16 + 1);
+    let border = Color::DarkGrey;
+    for i in 1..=inner {
+        canvas.put(x + i, y, '━', border);
+        canvas.put(x + i, bottom, '━', border);
+    }
+    for j in 1..bottom - y {
+        canvas.put(x, y + j, '┃', border);
+        canvas.put(right, y + j, '┃', border);
+    }
+    canvas.put(x, y, '┏', border);
+    canvas.put(right, y, '┓', border);
+    canvas.put(x, bottom, '┗', border);
+    canvas.put(right, bottom, '┛', border);
+
+    for (row, values) in grid.iter().enumerate() {
+        for (column, value) in values.iter().enumerate() {
+            let ch = match *value {
+                0 => continue,
+                GHOST => '░',
+                _ => '█',
+            };
+            for i in 0..cell {
+                let cx = x + 1 + column as u16 * cell + i;
+                canvas.put(cx, y + 1 + row as u16, ch, colour(*value));
+            }
+        }
+    }
+}
+
+// a piece lying flat in a two row box, for the hold and next panels
+fn draw_piece(canvas: &mut Canvas, (x, y): (u16, u16), cell: u16, piece: Tetrimino) {
+    let piece = piece.facing(Direction::Up);
+    // the straight piece lies along its second row, everything else starts at the top
+    let top = piece.cells((0, 0)).map(|(_, y, _)| y).min().unwrap_or(0);
+    for (cx, cy, value) in piece.cells((0, -top)) {
+        for i in 0..cell {
+            let px = x + cx as u16 * cell + i;
+            canvas.put(px, y + cy as u16, '█', colour(value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crossterm::style::Color;
+
+    use crate::{
+        game::{Game, GHOST},
+        piece::{Generator, Randomizer},
+        render::{colour, draw_game, Canvas, Cell, PANEL},
+    };
+
+    #[test]
+    fn only_changes_are_redrawn() {
+        let mut before = Canvas::new((4, 3));
+        before.text(0, 0, "ab", Color::Reset);
+        let mut after = before.clone();
+        after.put(1, 0, 'b', Color::Red);
+        after.put(3, 2, 'c', Color::Reset);
+        after.put(9, 9, 'd', Color::Reset);
+
+        let changes: Vec<_> = after.changes(&before).map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(changes, [(1, 0), (3, 2)]);
+    }
+
+    #[test]
+    fn pieces_keep_their_colours() {
+        let game = Game::with_generator(Generator::new(Randomizer::Bag, Some(1)));
+        let mut canvas = Canvas::new((80, 24));
+        draw_game(&mut canvas, &game);
+        // centred, with the hold panel and a gap to the left of the board
+        let (x, y) = ((80 - 48) / 2 + PANEL + 1, 1);
+        let piece = game.piece();
+        for (cx, cy, value) in piece.cells(game.position()) {
+            let cell = canvas
+                .get(x + 1 + 2 * cx as u16, y + 1 + cy as u16)
+                .unwrap();
+            assert_eq!(
+                cell,
+                Cell {
+                    ch: '█',
+                    colour: colour(piece.value())
+                }
+            );
+            assert_eq!(value, piece.value());
+        }
+        for (cx, cy, _) in piece.cells(game.ghost()) {
+            let cell = canvas
+                .get(x + 1 + 2 * cx as u16, y + 1 + cy as u16)
+                .unwrap();
+            assert_eq!(
+                cell,
+                Cell {
+                    ch: '░',
+                    colour: colour(GHOST)
+                }
+            );
+        }
+
+        let mut small = Canvas::new((30, 10));
+        draw_game(&mut small, &game);
+        assert_eq!(small.get(0, 0).unwrap().ch, 'm');
+    }
+}
@@ -0,0 +1,116 @@
+/// Whether a T piece was spun into place when it locked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spin {
+    None,
+    // only one of the corners the T points at is filled
+    Mini,
+    Full,
+}
+
+/// What locking a piece scored, only kept when it cleared lines or was a T-spin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clear {
+    pub lines: usize,
+    pub spin: Spin,
+    // clears in a row before this one, 0 for the first
+    pub combo: usize,
+    pub back_to_back: bool,
+    pub points: usize,
+}
+
+impl Clear {
+    /// Tetrises and T-spins that clear lines. Two of these with no easier clear between them
+    /// score back-to-back.
+    pub fn is_difficult(&self) -> bool {
+        self.lines == 4 || (self.spin != Spin::None && self.lines > 0)
+    }
+
+    /// How the clear is announced, "T-SPIN DOUBLE" or "TETRIS".
+    pub fn name(&self) -> String {
+        let lines = ["", "SINGLE", "DOUBLE", "TRIPLE", "TETRIS"][self.lines.min(4)];
+        match self.spin {
+            Spin::None => lines.to_string(),
+            Spin::Mini => format!("MINI T-SPIN {lines}").trim_end().to_string(),
+            Spin::Full => format!("T-SPIN {lines}").trim_end().to_string(),
+        }
+    }
+}
+
+// guideline points per level
+fn base_points(lines: usize, spin: Spin) -> usize {
+    match (spin, lines) {
+        (Spin::None, lines) => [0, 100, 300, 500, 800][lines.min(4)],
+        (Spin::Mini, 0) => 100,
+        (Spin::Mini, 1) => 200,
+        (Spin::Mini, _) => 400,
+        (Spin::Full, lines) => [400, 800, 1200, 1600][lines.min(3)],
+    }
+}
+
+/// Keeps track of combos and back-to-back chains from one piece to the next.
+#[derive(Debug, Clone, Default)]
+pub struct Scorer {
+    combo: Option<usize>,
+    back_to_back: bool,
+}
+
+impl Scorer {
+    /// Scores a piece locking with `lines` cleared at `level`.
+    pub fn lock(&mut self, lines: usize, spin: Spin, level: usize) -> Clear {
+        let mut clear = Clear {
+            lines,
+            spin,
+            combo: 0,
+            back_to_back: false,
+            points: base_points(lines, spin) * level,
+        };
+        if lines == 0 {
+            // a piece that clears nothing ends the combo but not a back-to-back chain
+            self.combo = None;
+            return clear;
+        }
+
+        let combo = self.combo.map_or(0, |combo| combo + 1);
+        self.combo = Some(combo);
+        clear.combo = combo;
+        clear.points += 50 * combo * level;
+
+        let difficult = clear.is_difficult();
+        if difficult && self.back_to_back {
+            clear.back_to_back = true;
+            clear.points += base_points(lines, spin) * level / 2;
+        }
+        self.back_to_back = difficult;
+        clear
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::scoring::{Scorer, Spin};
+
+    #[test]
+    fn combos_and_back_to_back() {
+        let mut scorer = Scorer::default();
+        let tetris = scorer.lock(4, Spin::None, 1);
+        assert_eq!(tetris.points, 800);
+        assert_eq!(tetris.name(), "TETRIS");
+
+        // 800 and half again, plus the first step of a combo
+        let again = scorer.lock(4, Spin::None, 1);
+        assert!(again.back_to_back);
+        assert_eq!(again.points, 1200 + 50);
+
+        let single = scorer.lock(1, Spin::None, 2);
+        assert!(!single.back_to_back);
+        assert_eq!(single.points, 200 + 2 * 50 * 2);
+
+        assert_eq!(scorer.lock(0, Spin::None, 1).points, 0);
+        let spin = scorer.lock(2, Spin::Full, 1);
+        assert_eq!((spin.points, spin.combo), (1200, 0));
+        assert_eq!(spin.name(), "T-SPIN DOUBLE");
+        // the single in between broke the chain, this one starts a new one
+        assert!(!spin.back_to_back);
+        assert!(scorer.lock(1, Spin::Mini, 1).back_to_back);
+    }
+}
@@ -0,0 +1,263 @@
+use std::{
+    collections::HashMap,
+    io::{self, IsTerminal, Write},
+    panic,
+    sync::Once,
+    time::Duration,
+};
+
+use crossterm::{
+    cursor,
+    event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags,
+        ModifierKeyCode, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute, queue,
+    style::{Color, Print, ResetColor, SetForegroundColor},
+    terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use crate::{game::Input, render::Canvas};
+
+/// What the keyboard did, already looked up in the [`Bindings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Press(Input),
+    Release(Input),
+    // a letter or number that isn't bound to anything, for answering questions
+    Char(char),
+    Quit,
+}
+
+/// Which keys do what. Each action can have any number of keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bindings(HashMap<KeyCode, Input>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let shift = |side| KeyCode::Modifier(side);
+        Self(HashMap::from([
+            (KeyCode::Left, Input::Left),
+            (KeyCode::Right, Input::Right),
+            (KeyCode::Down, Input::Down),
+            (KeyCode::Up, Input::RotateRight),
+            (KeyCode::Char('x'), Input::RotateRight),
+            (KeyCode::Char('z'), Input::RotateLeft),
+            (KeyCode::Char(' '), Input::HardDrop),
+            (KeyCode::Char('c'), Input::Hold),
+            (shift(ModifierKeyCode::LeftShift), Input::Hold),
+            (shift(ModifierKeyCode::RightShift), Input::Hold),
+        ]))
+    }
+}
+
+impl Bindings {
+    /// Rebinds from `action=key,key`, the keys given replace whatever the action had before.
+    pub fn bind(&mut self, binding: &str) -> Result<(), String> {
+        let (action, keys) = binding
+            .split_once('=')
+            .ok_or_else(|| format!("expected action=key, got '{binding}'"))?;
+        let input: Input = action.trim().parse()?;
+        let keys = keys
+            .split(',')
+            .map(parse_key)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.0.retain(|_, bound| *bound != input);
+        for key in keys {
+            self.0.insert(key, input);
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, key: KeyCode) -> Option<Input> {
+        let key = match key {
+            // shift turns letters into capitals
+            KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+            key => key,
+        };
+        self.0.get(&key).copied()
+    }
+}
+
+fn parse_key(name: &str) -> Result<KeyCode, String> {
+    let name = name.trim().to_lowercase();
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+    Ok(match name.as_str() {
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "lshift" => KeyCode::Modifier(ModifierKeyCode::LeftShift),
+        "rshift" => KeyCode::Modifier(ModifierKeyCode::RightShift),
+        "lctrl" => KeyCode::Modifier(ModifierKeyCode::LeftControl),
+        "rctrl" => KeyCode::Modifier(ModifierKeyCode::RightControl),
+        _ => return Err(format!("unknown key '{name}'")),
+    })
+}
+
+/// Raw mode on the alternate screen for as long as this lives. Terminals that speak the kitty
+/// keyboard protocol are asked to report releases too, which is what lets keys be held down.
+/// The terminal is put back when it's dropped, or if anything panics first.
+pub struct Terminal {
+    // whether releases get reported, without them every press is taken as a tap
+    releases: bool,
+    out: io::Stdout,
+    // what's on screen now, the next frame only sends what's different
+    front: Canvas,
+}
+
+impl Terminal {
+    pub fn new() -> io::Result<Self> {
+        if !io::stdin().is_terminal() {
+            return Err(io::Error::other("wetris needs to be played in a terminal"));
+        }
+        restore_on_panic();
+        terminal::enable_raw_mode()?;
+        let releases = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        let mut out = io::stdout();
+        if releases {
+            execute!(
+                out,
+                PushKeyboardEnhancementFlags(
+                    KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                        | KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES
+                )
+            )?;
+        }
+        execute!(out, EnterAlternateScreen, cursor::Hide)?;
+        Ok(Self {
+            releases,
+            out,
+            front: Canvas::new((0, 0)),
+        })
+    }
+
+    /// Whether keys can be held down, or the terminal only says when they're pressed.
+    pub fn reports_releases(&self) -> bool {
+        self.releases
+    }
+
+    /// Columns and rows, a new [`Canvas`] this size fills the screen.
+    pub fn size(&self) -> io::Result<(u16, u16)> {
+        terminal::size()
+    }
+
+    /// Puts `frame` on screen, only redrawing the cells that changed since the last one. A frame
+    /// of a different size, after the terminal was resized, starts again from a blank screen.
+    pub fn draw(&mut self, frame: Canvas) -> io::Result<()> {
+        if frame.size() != self.front.size() {
+            queue!(self.out, ResetColor, terminal::Clear(ClearType::All))?;
+            self.front = Canvas::new(frame.size());
+        }
+        let mut cursor = None;
+        let mut colour = None;
+        for (x, y, cell) in frame.changes(&self.front) {
+            // printing moves the cursor along, so a run of changes only needs the one move
+            if cursor != Some((x, y)) {
+                queue!(self.out, cursor::MoveTo(x, y))?;
+            }
+            if colour != Some(cell.colour) {
+                queue!(self.out, SetForegroundColor(cell.colour))?;
+                colour = Some(cell.colour);
+            }
+            queue!(self.out, Print(cell.ch))?;
+            cursor = Some((x + 1, y));
+        }
+        if colour.is_some() {
+            queue!(self.out, SetForegroundColor(Color::Reset))?;
+        }
+        self.front = frame;
+        self.out.flush()
+    }
+
+    /// Waits up to `timeout` for the keyboard, then returns everything it did since.
+    pub fn keys(&mut self, bindings: &Bindings, timeout: Duration) -> io::Result<Vec<Key>> {
+        let mut keys = vec![];
+        let mut wait = timeout;
+        while event::poll(wait)? {
+            wait = Duration::ZERO;
+            if let Event::Key(key) = event::read()? {
+                self.key(bindings, key, &mut keys);
+            }
+        }
+        Ok(keys)
+    }
+
+    fn key(&self, bindings: &Bindings, key: KeyEvent, keys: &mut Vec<Key>) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if key.code == KeyCode::Esc || ctrl && key.code == KeyCode::Char('c') {
+            if key.kind == KeyEventKind::Press {
+                keys.push(Key::Quit);
+            }
+            return;
+        }
+        let Some(input) = bindings.get(key.code) else {
+            if let (KeyCode::Char(c), KeyEventKind::Press) = (key.code, key.kind) {
+                keys.push(Key::Char(c.to_ascii_lowercase()));
+            }
+            return;
+        };
+        match key.kind {
+            KeyEventKind::Press if self.releases => keys.push(Key::Press(input)),
+            // the terminal repeats held keys itself, let it
+            KeyEventKind::Press => keys.extend([Key::Press(input), Key::Release(input)]),
+            KeyEventKind::Release => keys.push(Key::Release(input)),
+            KeyEventKind::Repeat => {}
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if self.releases {
+            let _ = execute!(self.out, PopKeyboardEnhancementFlags);
+        }
+        restore();
+    }
+}
+
+fn restore() {
+    let _ = execute!(io::stdout(), ResetColor, cursor::Show, LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
+
+fn restore_on_panic() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            hook(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use crossterm::event::KeyCode;
+
+    use crate::{game::Input, tui::Bindings};
+
+    #[test]
+    fn rebinding_replaces_the_old_keys() {
+        let mut bindings = Bindings::default();
+        assert_eq!(bindings.get(KeyCode::Char('Z')), Some(Input::RotateLeft));
+
+        bindings.bind("rotate-left=a, Up").unwrap();
+        assert_eq!(bindings.get(KeyCode::Char('z')), None);
+        assert_eq!(bindings.get(KeyCode::Char('a')), Some(Input::RotateLeft));
+        // a key only does one thing, so up stops rotating the other way
+        assert_eq!(bindings.get(KeyCode::Up), Some(Input::RotateLeft));
+
+        assert!(bindings.bind("spin=a").is_err());
+        assert!(bindings.bind("hold=f13").is_err());
+        assert!(bindings.bind("hold").is_err());
+    }
+}
@@ -0,0 +1,478 @@
+use std::{
+    hash::Hash,
+    io,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use wobby::{
+    matchmaker::{Joined, Matchmaker},
+    net::{self, Event},
+};
+
+use crate::{
+    controls::Controls,
+    game::{Game, Grid},
+    piece::{Generator, Randomizer},
+    render::{draw_message, draw_versus, Canvas, Opponent},
+    tui::{Bindings, Key, Terminal},
+    GRID_SIZE,
+};
+
+pub use wobby::net::Server;
+
+pub const DEFAULT_PORT: u16 = 3042;
+// how often the screen is redrawn while playing
+const FRAME: Duration = Duration::from_millis(16);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    // wait in the lobby for an opponent, sent again after a match to play another
+    Join {
+        name: String,
+    },
+    // the board as it looks now, active piece and all
+    Board {
+        board: Box<Grid>,
+        score: usize,
+        lines: usize,
+    },
+    // one piece locking and clearing `lines`
+    Cleared {
+        lines: usize,
+    },
+    ToppedOut,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    // everyone waiting for an opponent, you included
+    Lobby {
+        waiting: Vec<String>,
+    },
+    // both players get the same seed, and so the same pieces
+    Start {
+        opponent: String,
+        seed: u64,
+    },
+    Opponent {
+        board: Box<Grid>,
+        score: usize,
+        lines: usize,
+    },
+    // lines to add to the bottom of your board, with a gap in the `hole` column
+    Garbage {
+        lines: usize,
+        hole: usize,
+    },
+    Rejected {
+        reason: String,
+    },
+    // `forfeit` if the loser left part way through
+    Finished {
+        won: bool,
+        forfeit: bool,
+    },
+}
+
+/// Lines of garbage sent for clearing `lines` at once, singles don't send any.
+pub fn garbage(lines: usize) -> usize {
+    [0, 0, 1, 2, 4][lines.min(4)]
+}
+
+/// The server's side of versus with no network attached: passes each board on to the other
+/// player and turns line clears into garbage for them. Each call returns the messages to send
+/// and who to send them to.
+pub struct Referee<T> {
+    rng: StdRng,
+    players: Matchmaker<T>,
+}
+
+impl<T: Copy + Eq + Hash> Referee<T> {
+    pub fn new(rng: StdRng) -> Self {
+        Self {
+            rng,
+            players: Matchmaker::new(),
+        }
+    }
+
+    pub fn handle(&mut self, from: T, message: ClientMessage) -> Vec<(T, ServerMessage)> {
+        let Some(opponent) = self.players.opponent(from) else {
+            return match message {
+                ClientMessage::Join { name } => self.join(from, &name),
+                // anything else outside a match, like a last board after it ended, is dropped
+                _ => vec![],
+            };
+        };
+        match message {
+            // already playing
+            ClientMessage::Join { .. } => vec![],
+            ClientMessage::Board {
+                board,
+                score,
+                lines,
+            } => vec![(
+                opponent,
+                ServerMessage::Opponent {
+                    board,
+                    score,
+                    lines,
+                },
+            )],
+            ClientMessage::Cleared { lines } => match garbage(lines) {
+                0 => vec![],
+                lines => vec![(
+                    opponent,
+                    ServerMessage::Garbage {
+                        lines,
+                        hole: self.rng.gen_range(0..GRID_SIZE.0),
+                    },
+                )],
+            },
+            ClientMessage::ToppedOut => {
+                self.players.end(from);
+                finished(opponent, from, false)
+            }
+        }
+    }
+
+    fn join(&mut self, from: T, name: &str) -> Vec<(T, ServerMessage)> {
+        let Joined::Paired(a, b) = self.players.join(from, name) else {
+            return self.lobby_update();
+        };
+        // the same seed for both, so they get the same pieces
+        let seed = self.rng.gen();
+        let start = |opponent| ServerMessage::Start {
+            opponent: self.players.name(opponent).to_string(),
+            seed,
+        };
+        let mut out = vec![(a, start(b)), (b, start(a))];
+        out.extend(self.lobby_update());
+        out
+    }
+
+    /// Someone disconnected. If they were in a match it goes to the other player.
+    pub fn leave(&mut self, who: T) -> Vec<(T, ServerMessage)> {
+        let mut out = match self.players.leave(who) {
+            Some(opponent) => vec![(
+                opponent,
+                ServerMessage::Finished {
+                    won: true,
+                    forfeit: true,
+                },
+            )],
+            None => vec![],
+        };
+        out.extend(self.lobby_update());
+        out
+    }
+
+    fn lobby_update(&self) -> Vec<(T, ServerMessage)> {
+        self.players
+            .waiting(|waiting| ServerMessage::Lobby { waiting })
+    }
+}
+
+fn finished<T>(winner: T, loser: T, forfeit: bool) -> Vec<(T, ServerMessage)> {
+    vec![
+        (winner, ServerMessage::Finished { won: true, forfeit }),
+        (
+            loser,
+            ServerMessage::Finished {
+                won: false,
+                forfeit,
+            },
+        ),
+    ]
+}
+
+/// Listens for versus players on `addr`, the server binary is this and not much else.
+pub fn serve(addr: &str) -> io::Result<Server> {
+    let mut referee = Referee::new(StdRng::from_entropy());
+    Server::start(addr, move |from, event| match event {
+        Event::Joined => vec![],
+        Event::Message(message) => referee.handle(from, message),
+        Event::Unreadable(e) => vec![(
+            from,
+            ServerMessage::Rejected {
+                reason: format!("could not read that: {e}"),
+            },
+        )],
+        Event::Left => referee.leave(from),
+    })
+}
+
+pub type Connection = net::Connection<ClientMessage>;
+
+enum Phase {
+    Lobby(Vec<String>),
+    Playing,
+    Finished { won: bool, forfeit: bool },
+}
+
+/// What the client has been told about the other player.
+struct Rival {
+    name: String,
+    board: Grid,
+    score: usize,
+    lines: usize,
+}
+
+// runs one thing that might lock a piece, returning how many lines it cleared
+fn cleared(game: &mut Game, action: impl FnOnce(&mut Game)) -> usize {
+    let before = game.lines();
+    action(game);
+    game.lines() - before
+}
+
+/// Joins the server at `addr` as `name` and plays matches until the player quits.
+pub fn play_versus(
+    addr: &str,
+    name: &str,
+    mut controls: Controls,
+    bindings: &Bindings,
+) -> io::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let connection = Connection::connect(addr, move |message| {
+        let _ = tx.send(message);
+    })?;
+    let join = ClientMessage::Join {
+        name: name.to_string(),
+    };
+    connection.send(&join);
+
+    let mut terminal = Terminal::new()?;
+    let mut phase = Phase::Lobby(vec![]);
+    let mut game = Game::new();
+    let mut rival = Rival {
+        name: String::new(),
+        board: [[0; GRID_SIZE.0]; GRID_SIZE.1],
+        score: 0,
+        lines: 0,
+    };
+    // the last board sent, so an unchanged one isn't sent again
+    let mut sent = None;
+    let mut notice: Option<String> = None;
+    let mut last = Instant::now();
+
+    loop {
+        for message in rx.try_iter() {
+            let Some(message) = message else {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionAborted,
+                    "lost the connection to the server",
+                ));
+            };
+            match message {
+                ServerMessage::Lobby { waiting } => {
+                    if let Phase::Lobby(_) = phase {
+                        phase = Phase::Lobby(waiting);
+                    }
+                }
+                ServerMessage::Start { opponent, seed } => {
+                    game = Game::with_generator(Generator::new(Randomizer::Bag, Some(seed)));
+                    controls = Controls::new(controls.handling());
+                    rival = Rival {
+                        name: opponent,
+                        board: [[0; GRID_SIZE.0]; GRID_SIZE.1],
+                        score: 0,
+                        lines: 0,
+                    };
+                    sent = None;
+                    notice = None;
+                    phase = Phase::Playing;
+                }
+                ServerMessage::Opponent {
+                    board,
+                    score,
+                    lines,
+                } => {
+                    rival.board = *board;
+                    rival.score = score;
+                    rival.lines = lines;
+                }
+                ServerMessage::Garbage { lines, hole } => game.add_garbage(lines, hole),
+                ServerMessage::Rejected { reason } => notice = Some(reason),
+                ServerMessage::Finished { won, forfeit } => {
+                    phase = Phase::Finished { won, forfeit };
+                }
+            }
+        }
+
+        for key in terminal.keys(bindings, FRAME)? {
+            match (key, &phase) {
+                (Key::Quit, _) => return Ok(()),
+                (Key::Press(input), Phase::Playing) => controls.press(input),
+                (Key::Release(input), _) => controls.release(input),
+                (Key::Char('y'), Phase::Finished { .. }) => {
+                    phase = Phase::Lobby(vec![]);
+                    connection.send(&join);
+                }
+                (Key::Char('n'), Phase::Finished { .. }) => return Ok(()),
+                _ => {}
+            }
+        }
+
+        let now = Instant::now();
+        let elapsed = now - last;
+        last = now;
+        if matches!(phase, Phase::Playing) && !game.is_over() {
+            let mut clears = vec![];
+            for input in controls.update(elapsed) {
+                clears.push(cleared(&mut game, |game| {
+                    game.step(input);
+                }));
+            }
+            clears.push(cleared(&mut game, |game| game.tick(elapsed)));
+            for lines in clears.into_iter().filter(|lines| *lines > 0) {
+                connection.send(&ClientMessage::Cleared { lines });
+            }
+
+            let board = game.view();
+            if sent != Some(board) {
+                connection.send(&ClientMessage::Board {
+                    board: Box::new(board),
+                    score: game.score(),
+                    lines: game.lines(),
+                });
+                sent = Some(board);
+            }
+            if game.is_over() {
+                connection.send(&ClientMessage::ToppedOut);
+            }
+        }
+
+        let mut frame = Canvas::new(terminal.size()?);
+        match &phase {
+            Phase::Lobby(waiting) => {
+                let mut lines = vec!["wetris versus", "", "waiting for an opponent..."];
+                let lobby = format!("in the lobby: {}", waiting.join(", "));
+                if !waiting.is_empty() {
+                    lines.push(&lobby);
+                }
+                lines.extend(["", "(esc) quit"]);
+                draw_message(&mut frame, &lines);
+            }
+            _ => {
+                let result = match phase {
+                    Phase::Finished { won: true, forfeit } if forfeit => {
+                        format!("{} left, you win!", rival.name)
+                    }
+                    Phase::Finished { won: true, .. } => "you win!".to_string(),
+                    Phase::Finished { won: false, .. } => format!("{} wins!", rival.name),
+                    _ => String::new(),
+                };
+                let mut message = vec![];
+                if let Some(notice) = &notice {
+                    message.push(notice.as_str());
+                }
+                if !result.is_empty() {
+                    message.extend([result.as_str(), "play again? (y/n)"]);
+                }
+                let opponent = Opponent {
+                    name: &rival.name,
+                    board: &rival.board,
+                    score: rival.score,
+                    lines: rival.lines,
+                };
+                draw_versus(&mut frame, &game, opponent, &message);
+            }
+        }
+        terminal.draw(frame)?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        versus::{ClientMessage, Referee, ServerMessage},
+        GRID_SIZE,
+    };
+
+    fn join(name: &str) -> ClientMessage {
+        ClientMessage::Join {
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn doubles_and_up_send_garbage() {
+        let mut referee = Referee::new(StdRng::seed_from_u64(1));
+        assert_eq!(
+            referee.handle(1, join("ann")),
+            vec![(
+                1,
+                ServerMessage::Lobby {
+                    waiting: vec!["ann".to_string()]
+                }
+            )]
+        );
+        let out = referee.handle(2, join("bob"));
+        let seeds: Vec<u64> = out
+            .iter()
+            .filter_map(|(_, m)| match m {
+                ServerMessage::Start { seed, .. } => Some(*seed),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(seeds.len(), 2);
+        assert_eq!(seeds[0], seeds[1]);
+
+        let board = ClientMessage::Board {
+            board: Box::new([[1; GRID_SIZE.0]; GRID_SIZE.1]),
+            score: 10,
+            lines: 0,
+        };
+        assert!(matches!(
+            referee.handle(2, board)[..],
+            [(1, ServerMessage::Opponent { score: 10, .. })]
+        ));
+        assert!(referee
+            .handle(1, ClientMessage::Cleared { lines: 1 })
+            .is_empty());
+        assert!(matches!(
+            referee.handle(1, ClientMessage::Cleared { lines: 4 })[..],
+            [(2, ServerMessage::Garbage { lines: 4, .. })]
+        ));
+
+        let out = referee.handle(2, ClientMessage::ToppedOut);
+        assert!(out.contains(&(
+            1,
+            ServerMessage::Finished {
+                won: true,
+                forfeit: false
+            }
+        )));
+        assert!(out.contains(&(
+            2,
+            ServerMessage::Finished {
+                won: false,
+                forfeit: false
+            }
+        )));
+        // the match is over, so nothing goes anywhere
+        assert!(referee
+            .handle(1, ClientMessage::Cleared { lines: 4 })
+            .is_empty());
+
+        // leaving part way through the next one hands it to the other player
+        referee.handle(2, join("bob"));
+        referee.handle(1, join("ann"));
+        assert_eq!(
+            referee.leave(1),
+            vec![(
+                2,
+                ServerMessage::Finished {
+                    won: true,
+                    forfeit: true
+                }
+            )]
+        );
+    }
+}
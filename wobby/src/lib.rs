@@ -0,0 +1,2 @@
+pub mod matchmaker;
+pub mod net;
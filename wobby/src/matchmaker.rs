@@ -0,0 +1,120 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// What became of someone joining.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Joined<T> {
+    // they're in a match already, so nothing changes
+    Playing,
+    // in line for the next opponent to come along
+    Waiting,
+    // the first two in line, now playing each other
+    Paired(T, T),
+}
+
+/// Who's waiting for a match and who's playing who, for two player games. `T` identifies a
+/// player, the servers use their endpoint. Anything else about a match is up to the game.
+#[derive(Debug, Clone)]
+pub struct Matchmaker<T> {
+    names: HashMap<T, String>,
+    waiting: Vec<T>,
+    opponents: HashMap<T, T>,
+}
+
+impl<T> Default for Matchmaker<T> {
+    fn default() -> Self {
+        Self {
+            names: HashMap::new(),
+            waiting: vec![],
+            opponents: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash> Matchmaker<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts `who` in line as `name`, they join again after a match to play another.
+    pub fn join(&mut self, who: T, name: &str) -> Joined<T> {
+        if self.opponents.contains_key(&who) {
+            return Joined::Playing;
+        }
+        let name = match name.trim() {
+            "" => "player",
+            name => name,
+        };
+        self.names.insert(who, name.to_string());
+        if !self.waiting.contains(&who) {
+            self.waiting.push(who);
+        }
+        if self.waiting.len() < 2 {
+            return Joined::Waiting;
+        }
+        let (a, b) = (self.waiting.remove(0), self.waiting.remove(0));
+        self.opponents.insert(a, b);
+        self.opponents.insert(b, a);
+        Joined::Paired(a, b)
+    }
+
+    pub fn name(&self, who: T) -> &str {
+        self.names.get(&who).map_or("", String::as_str)
+    }
+
+    pub fn opponent(&self, who: T) -> Option<T> {
+        self.opponents.get(&who).copied()
+    }
+
+    /// Ends the match `who` is in for both players, returning who they were playing.
+    pub fn end(&mut self, who: T) -> Option<T> {
+        let opponent = self.opponents.remove(&who)?;
+        self.opponents.remove(&opponent);
+        Some(opponent)
+    }
+
+    /// Forgets `who`, ending their match if they were in one. Returns who they were playing, with
+    /// their name still known so the game can tell them they won.
+    pub fn leave(&mut self, who: T) -> Option<T> {
+        self.names.remove(&who);
+        self.waiting.retain(|w| *w != who);
+        self.end(who)
+    }
+
+    /// `message` listing everyone in line, for each of them.
+    pub fn waiting<M>(&self, message: impl Fn(Vec<String>) -> M) -> Vec<(T, M)> {
+        let names: Vec<String> = self
+            .waiting
+            .iter()
+            .map(|w| self.name(*w).to_string())
+            .collect();
+        self.waiting
+            .iter()
+            .map(|w| (*w, message(names.clone())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::matchmaker::{Joined, Matchmaker};
+
+    #[test]
+    fn pairs_players_in_the_order_they_join() {
+        let mut players = Matchmaker::new();
+        assert_eq!(players.join(1, "ann"), Joined::Waiting);
+        assert_eq!(players.join(2, " "), Joined::Paired(1, 2));
+        assert_eq!(players.name(2), "player");
+        assert_eq!(players.join(3, "cat"), Joined::Waiting);
+        assert_eq!(players.waiting(|w| w), [(3, vec!["cat".to_string()])]);
+        assert_eq!(players.join(1, "ann"), Joined::Playing);
+
+        // bob leaving ends the match, ann's name is kept for telling them they won
+        assert_eq!(players.leave(2), Some(1));
+        assert_eq!(players.name(1), "ann");
+        assert_eq!(players.opponent(1), None);
+        assert_eq!(players.join(1, "ann"), Joined::Paired(3, 1));
+        assert_eq!(players.end(1), Some(3));
+        assert_eq!(players.opponent(3), None);
+        assert!(players.waiting(|w| w).is_empty());
+    }
+}
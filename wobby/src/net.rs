@@ -0,0 +1,169 @@
+use std::{io, marker::PhantomData, net::SocketAddr};
+
+pub use message_io::network::Endpoint;
+use message_io::{
+    network::{NetEvent, Transport},
+    node::{self, NodeHandler, NodeTask},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+fn encode(message: &impl Serialize) -> Vec<u8> {
+    serde_json::to_vec(message).expect("messages should always serialize")
+}
+
+/// Something a player did, as the server sees it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<C> {
+    Joined,
+    Message(C),
+    // sent something that wasn't a message, with what was wrong with it
+    Unreadable(String),
+    Left,
+}
+
+/// Accepts players over TCP and passes everything they do on to the game. Messages go both ways
+/// as JSON, one per frame.
+pub struct Server {
+    handler: NodeHandler<()>,
+    task: NodeTask,
+    addr: SocketAddr,
+}
+
+impl Server {
+    /// `handle` gets who did something and what, and returns the messages to send and who to
+    /// send them to.
+    pub fn start<C, S>(
+        addr: &str,
+        mut handle: impl FnMut(Endpoint, Event<C>) -> Vec<(Endpoint, S)> + Send + 'static,
+    ) -> io::Result<Self>
+    where
+        C: DeserializeOwned,
+        S: Serialize,
+    {
+        let (handler, listener) = node::split::<()>();
+        let (_, addr) = handler.network().listen(Transport::FramedTcp, addr)?;
+
+        let network = handler.clone();
+        let task = listener.for_each_async(move |event| {
+            let out = match event.network() {
+                NetEvent::Connected(_, _) => unreachable!(), // only for connections we make
+                NetEvent::Accepted(endpoint, _) => handle(endpoint, Event::Joined),
+                NetEvent::Message(endpoint, data) => match serde_json::from_slice(data) {
+                    Ok(message) => handle(endpoint, Event::Message(message)),
+                    Err(e) => handle(endpoint, Event::Unreadable(e.to_string())),
+                },
+                NetEvent::Disconnected(endpoint) => handle(endpoint, Event::Left),
+            };
+            for (to, message) in out {
+                network.network().send(to, &encode(&message));
+            }
+        });
+        Ok(Self {
+            handler,
+            task,
+            addr,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Blocks until the server is stopped.
+    pub fn wait(mut self) {
+        self.task.wait();
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.handler.stop();
+    }
+}
+
+/// A player's end, sending `C`s to the server.
+pub struct Connection<C> {
+    handler: NodeHandler<()>,
+    server: Endpoint,
+    _task: NodeTask,
+    sends: PhantomData<fn(&C)>,
+}
+
+impl<C: Serialize> Connection<C> {
+    /// `on_message` gets called with every message from the server, then with None once the
+    /// connection is gone.
+    pub fn connect<S: DeserializeOwned>(
+        addr: &str,
+        mut on_message: impl FnMut(Option<S>) + Send + 'static,
+    ) -> io::Result<Self> {
+        let (handler, listener) = node::split::<()>();
+        let (server, _) = handler.network().connect_sync(Transport::FramedTcp, addr)?;
+        let task = listener.for_each_async(move |event| match event.network() {
+            NetEvent::Message(_, data) => {
+                if let Ok(message) = serde_json::from_slice(data) {
+                    on_message(Some(message));
+                }
+            }
+            NetEvent::Disconnected(_) => on_message(None),
+            _ => {}
+        });
+        Ok(Self {
+            handler,
+            server,
+            _task: task,
+            sends: PhantomData,
+        })
+    }
+
+    pub fn send(&self, message: &C) {
+        self.handler.network().send(self.server, &encode(message));
+    }
+}
+
+impl<C> Drop for Connection<C> {
+    fn drop(&mut self) {
+        self.handler.stop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::mpsc, time::Duration};
+
+    use crate::net::{Connection, Event, Server};
+
+    #[test]
+    fn over_localhost() {
+        // greets whoever joins, shouts back whatever it's sent, and says when it can't read
+        // something
+        let server = Server::start("127.0.0.1:0", |from, event: Event<String>| match event {
+            Event::Joined => vec![(from, "hi".to_string())],
+            Event::Message(text) => vec![(from, text.to_uppercase())],
+            Event::Unreadable(_) => vec![(from, "what?".to_string())],
+            Event::Left => vec![],
+        })
+        .unwrap();
+        let (tx, rx) = mpsc::channel();
+        let words = Connection::connect(&server.addr().to_string(), move |m: Option<String>| {
+            let _ = tx.send(m);
+        })
+        .unwrap();
+        let (tx, numbers_rx) = mpsc::channel();
+        let numbers = Connection::connect(&server.addr().to_string(), move |m: Option<String>| {
+            let _ = tx.send(m);
+        })
+        .unwrap();
+        let next =
+            |rx: &mpsc::Receiver<Option<String>>| rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        assert_eq!(next(&rx).as_deref(), Some("hi"));
+        assert_eq!(next(&numbers_rx).as_deref(), Some("hi"));
+        words.send(&"hello".to_string());
+        assert_eq!(next(&rx).as_deref(), Some("HELLO"));
+        numbers.send(&42);
+        assert_eq!(next(&numbers_rx).as_deref(), Some("what?"));
+
+        drop(server);
+        assert_eq!(next(&rx), None);
+    }
+}
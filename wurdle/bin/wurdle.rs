@@ -1,35 +1,451 @@
-use std::{
-    collections::{HashMap, HashSet},
-    io::{self, stdin, Read},
+use std::{env, path::PathBuf, process, time::Instant};
+
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use wurdle::{
+    absurd::ABSURD_TRIES,
+    config::Config,
+    daily,
+    dashboard::{to_csv, Dashboard},
+    dictionary::Dictionary,
+    duel::{self, play_duel, DEFAULT_PORT},
+    multi::{default_tries, BOARD_COUNTS},
+    play_with,
+    race::{best_text, play_race, Race, RaceRecord},
+    share,
+    solver::{benchmark, Solver, Strategy},
+    stats::Stats,
+    stats_text,
+    theme::Theme,
+    PlayOptions,
 };
 
-use wurdle::{play, wurdle_words};
+/// Guess the hidden word, green means right letter in the right spot and yellow means right
+/// letter in the wrong spot.
+#[derive(Parser)]
+#[command(name = "wurdle", version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    game: GameArgs,
+
+    #[command(flatten)]
+    play: PlayArgs,
+}
+
+#[derive(Args)]
+struct GameArgs {
+    /// How many guesses you get [default: 6, 5 more than the number of boards, or 10 if absurd]
+    #[arg(short, long, global = true, value_parser = parse_tries)]
+    tries: Option<usize>,
+
+    /// Word length, from 4 to 8 letters
+    #[arg(short = 'n', long, default_value_t = 5, global = true)]
+    length: usize,
+
+    /// Every guess has to reuse revealed greens and yellows
+    #[arg(long, global = true)]
+    hard: bool,
+
+    /// File of possible answers, one word per line
+    #[arg(long, global = true, value_name = "FILE")]
+    answers: Option<PathBuf>,
+
+    /// File of extra words accepted as guesses, one word per line
+    #[arg(long, global = true, value_name = "FILE", requires = "answers")]
+    guesses: Option<PathBuf>,
+
+    /// Colours to use: classic, contrast (orange and blue) or mono (no colour)
+    #[arg(long, global = true)]
+    theme: Option<Theme>,
+}
+
+#[derive(Args)]
+struct PlayArgs {
+    /// Only pick words containing this letter
+    #[arg(short, long)]
+    letter: Option<char>,
+
+    /// Seed for the random words, the same seed gives the same words
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Also write the share block to this file when a game ends
+    #[arg(long, value_name = "FILE")]
+    share: Option<PathBuf>,
+
+    /// Show statistics after each game
+    #[arg(short, long)]
+    show_stats: bool,
+
+    /// Guess 2, 4 or 8 words at once, each on its own board
+    #[arg(short, long, default_value_t = 1)]
+    boards: usize,
+
+    /// The answer changes to dodge every guess, keeping as many words possible as it can
+    #[arg(long)]
+    absurd: bool,
+}
+
+#[derive(Args)]
+struct SolverArgs {
+    /// Always open with this word
+    #[arg(long, value_name = "WORD")]
+    opener: Option<String>,
+
+    /// How the solver picks its next guess: entropy, candidates or first
+    #[arg(long, default_value = "entropy")]
+    strategy: Strategy,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Export {
+    Csv,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play random words (the default)
+    Play(PlayArgs),
+    /// Play today's puzzle, or a past one with --date
+    Daily {
+        /// Replay the puzzle from this date (YYYY-MM-DD)
+        #[arg(long)]
+        date: Option<NaiveDate>,
+
+        /// Also write the share block to this file when the game ends
+        #[arg(long, value_name = "FILE")]
+        share: Option<PathBuf>,
+
+        /// Show statistics after the game
+        #[arg(short, long)]
+        show_stats: bool,
+    },
+    /// Let the solver play WORD and see how many guesses it needs
+    Solve {
+        word: String,
+
+        #[command(flatten)]
+        solver: SolverArgs,
+    },
+    /// Run the solver against every answer and report how it did
+    Bench {
+        #[command(flatten)]
+        solver: SolverArgs,
+    },
+    /// Show your statistics
+    Stats {
+        /// Forget all statistics (the old file is kept as a .bak)
+        #[arg(long)]
+        reset: bool,
+
+        /// Show statistics for games played on this many boards
+        #[arg(short, long, default_value_t = 1)]
+        boards: usize,
+
+        /// Print every game ever played instead, as csv or json
+        #[arg(long, value_name = "FORMAT")]
+        export: Option<Export>,
+    },
+    /// Solve as many words as you can before the time runs out
+    TimeAttack {
+        /// How long you get
+        #[arg(short, long, default_value_t = 3)]
+        minutes: u64,
+    },
+    /// Solve the same ten words as fast as you can
+    Speedrun {
+        /// Which ten words, the same seed always gives the same ones
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Host duels for other players to join
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value_t = format!("0.0.0.0:{DEFAULT_PORT}"))]
+        addr: String,
+    },
+    /// Race someone else to the same word over the network
+    Duel {
+        /// The server to join
+        #[arg(default_value_t = format!("127.0.0.1:{DEFAULT_PORT}"))]
+        addr: String,
+
+        /// What your opponent sees you as [default: your user name]
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Show the current theme, or save THEME as the one to use from now on
+    Theme { theme: Option<Theme> },
+}
+
+fn parse_tries(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(0) => Err("you need at least one guess".to_string()),
+        Ok(tries) => Ok(tries),
+        Err(e) => Err(e.to_string()),
+    }
+}
 
 fn main() {
+    let cli = Cli::parse();
+    let game = cli.game;
 
-    
-    // stdin().read_line(&mut "".to_string());
+    let dictionary = match &game.answers {
+        Some(answers) => Dictionary::from_files(answers, game.guesses.as_deref(), game.length),
+        None => Dictionary::builtin(game.length),
+    }
+    .unwrap_or_else(|e| exit(e));
 
-    let tries = 6;
-    let available_letters: HashSet<char> = HashSet::from_iter("".chars());
-    let words_vec: Vec<String> = wurdle_words::WURDLE_WURDS
-        .split("\n")
-        .map(|s| s.to_uppercase())
+    let config = Config::read();
+    let theme = game.theme.unwrap_or_else(|| config.theme());
+
+    let (boards, absurd) = match &cli.command {
+        None => (cli.play.boards, cli.play.absurd),
+        Some(Command::Play(args)) => (args.boards, args.absurd),
+        _ => (1, false),
+    };
+    let tries = if absurd {
+        ABSURD_TRIES
+    } else {
+        default_tries(boards)
+    };
+    let options = PlayOptions {
+        tries: game.tries.unwrap_or(tries),
+        hard: game.hard,
+        theme,
+        ..Default::default()
+    };
+    match cli.command {
+        None => play(&dictionary, options, cli.play),
+        Some(Command::Play(args)) => play(&dictionary, options, args),
+        Some(Command::Daily {
+            date,
+            share,
+            show_stats,
+        }) => {
+            let date = date.unwrap_or_else(daily::today);
+            // a date with no puzzle is a usage error, not a game
+            if let Err(e) = daily::daily(date, &dictionary.answers) {
+                exit(e);
+            }
+            play_with(
+                &dictionary,
+                &PlayOptions {
+                    daily: Some(date),
+                    share_file: share,
+                    show_stats,
+                    ..options
+                },
+            );
+        }
+        Some(Command::Solve { word, solver: args }) => {
+            solve(&solver(&dictionary, args), &word, options.tries, game.hard)
+        }
+        Some(Command::Bench { solver: args }) => {
+            bench(&solver(&dictionary, args), options.tries, game.hard)
+        }
+        Some(Command::Stats {
+            reset,
+            boards,
+            export,
+        }) => {
+            let tries = game.tries.unwrap_or(default_tries(boards));
+            match export {
+                Some(format) => export_stats(format),
+                None => stats(&dictionary, tries, reset, boards, theme),
+            }
+        }
+        Some(Command::TimeAttack { minutes }) => {
+            race(&dictionary, Race::TimeAttack { minutes }, options)
+        }
+        Some(Command::Speedrun { seed }) => race(&dictionary, Race::Speedrun { seed }, options),
+        Some(Command::Serve { addr }) => {
+            let server = duel::serve(&addr, &dictionary, options.tries)
+                .unwrap_or_else(|e| exit(format!("could not listen on {addr}: {e}")));
+            println!("waiting for players on {}", server.addr());
+            server.wait();
+        }
+        Some(Command::Duel { addr, name }) => {
+            let name = name
+                .or_else(|| env::var("USER").ok())
+                .unwrap_or_else(|| "player".to_string());
+            if let Err(e) = play_duel(&addr, &name, theme) {
+                exit(format!("could not play on {addr}: {e}"));
+            }
+        }
+        Some(Command::Theme { theme: None }) => {
+            for option in Theme::ALL {
+                let current = if option == theme { " (current)" } else { "" };
+                println!("{option}{current}");
+            }
+        }
+        Some(Command::Theme { theme: Some(theme) }) => {
+            let mut config = config;
+            config.theme = Some(theme);
+            if let Err(e) = config.save() {
+                exit(format!("could not save the theme: {e}"));
+            }
+            println!("using the {theme} theme from now on");
+        }
+    }
+}
+
+fn exit(e: impl std::fmt::Display) -> ! {
+    eprintln!("{e}");
+    process::exit(1);
+}
+
+fn play(dictionary: &Dictionary, options: PlayOptions, args: PlayArgs) {
+    if !BOARD_COUNTS.contains(&args.boards) {
+        exit("you can play on 1, 2, 4 or 8 boards");
+    }
+    if args.boards > 1 && options.hard {
+        exit("hard mode only works with one board");
+    }
+    if args.absurd && (args.boards > 1 || options.hard || args.letter.is_some()) {
+        exit("absurd mode can't be combined with boards, hard mode or a letter");
+    }
+    let minion = args.letter.map(|c| c.to_ascii_uppercase());
+    if let Some(minion) = minion {
+        if !dictionary.answers.iter().any(|w| w.contains(minion)) {
+            exit(format!(
+                "there are no {} letter words with {minion}",
+                dictionary.length
+            ));
+        }
+    }
+    play_with(
+        dictionary,
+        &PlayOptions {
+            minion,
+            seed: args.seed,
+            share_file: args.share,
+            show_stats: args.show_stats,
+            boards: args.boards,
+            absurd: args.absurd,
+            ..options
+        },
+    );
+}
+
+fn race(dictionary: &Dictionary, race: Race, options: PlayOptions) {
+    if race == (Race::TimeAttack { minutes: 0 }) {
+        exit("a time attack needs at least a minute");
+    }
+    if let Err(e) = play_race(dictionary, race, &options) {
+        exit(format!("could not play the {race}: {e}"));
+    }
+}
+
+fn solver(dictionary: &Dictionary, args: SolverArgs) -> Solver {
+    let mut solver = Solver::new(dictionary).with_strategy(args.strategy);
+    if let Some(opener) = args.opener {
+        solver = solver.with_opener(&opener);
+    }
+    solver
+}
+
+fn solve(solver: &Solver, answer: &str, tries: usize, hard: bool) {
+    let answer = answer.to_uppercase();
+    let attempts = solver.solve(&answer, tries, hard);
+    for (i, attempt) in attempts.iter().enumerate() {
+        let squares: String = attempt.letters.iter().map(|l| share::square(*l)).collect();
+        println!("{}) {} {squares}", i + 1, attempt.guess);
+    }
+    match attempts.last() {
+        Some(last) if last.is_correct() => {
+            println!("solved {answer} in {} guesses", attempts.len())
+        }
+        _ => exit(format!("could not solve {answer} in {tries} guesses")),
+    }
+}
+
+fn bench(solver: &Solver, tries: usize, hard: bool) {
+    let started = Instant::now();
+    println!(
+        "solving {} words with the {} strategy, opening with {}{}...",
+        solver.answers().len(),
+        solver.strategy(),
+        solver.opener(hard),
+        if hard { " in hard mode" } else { "" }
+    );
+    let report = benchmark(solver, tries, hard, 10);
+
+    let widest = report
+        .distribution
+        .iter()
+        .max()
+        .copied()
+        .unwrap_or(0)
+        .max(1);
+    println!("\nGUESS DISTRIBUTION\n==================");
+    for (i, n) in report.distribution.iter().enumerate() {
+        println!("{} {:>5} {}", i + 1, n, "#".repeat(n * 50 / widest));
+    }
+    println!(
+        "X {:>5} {}",
+        report.failures.len(),
+        "#".repeat(report.failures.len() * 50 / widest)
+    );
+    println!(
+        "\nsolved {}/{} in {:.3} guesses on average ({:.1}s)",
+        report.solved(),
+        report.games,
+        report.mean(),
+        started.elapsed().as_secs_f64()
+    );
+    println!(
+        "worst: {}",
+        report
+            .worst
+            .iter()
+            .map(|(word, guesses)| format!("{word} ({guesses})"))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    if !report.failures.is_empty() {
+        println!("failed: {}", report.failures.join(", "));
+    }
+}
+
+fn stats(dictionary: &Dictionary, tries: usize, reset: bool, boards: usize, theme: Theme) {
+    let mut db = Stats::read();
+    if reset {
+        if let Err(e) = db.reset() {
+            exit(format!("could not reset {}: {e}", db.path().display()));
+        }
+        println!("statistics reset");
+        return;
+    }
+    let summary = db.summary_for_boards(dictionary.length, &dictionary.name, boards);
+    let dashboard = Dashboard::new(db.records_for(dictionary.length, &dictionary.name, boards));
+    println!("{}", stats_text(&summary, tries, None, theme));
+    print!("{}", dashboard.text(&summary, theme));
+
+    let bests: Vec<&RaceRecord> = db
+        .bests
+        .iter()
+        .filter(|b| b.length == dictionary.length && b.dictionary == dictionary.name)
         .collect();
+    if !bests.is_empty() {
+        println!("\nPERSONAL BESTS\n==============");
+    }
+    for best in bests {
+        println!("{}", best_text(best));
+    }
+}
 
-    // let mut hashm = HashMap::<char, usize>::new();
-    // for word in &words_vec {
-    //     for c in word.chars() {
-    //         if !hashm.contains_key(&c) {
-    //             hashm.insert(c, 0);
-    //         }
-    //         hashm.insert(c, hashm[&c] + 1);
-    //     }
-    // }
-    // let mut k = hashm.iter().map(|(c, u)| (*c, *u)).collect::<Vec<(char, usize)>>();
-    // k.sort_by(|a, b| a.1.cmp(&b.1));
-    // println!("{:#?}", k);
-    play(tries, 
-        // available_letters, 
-        words_vec, None, false);
+fn export_stats(format: Export) {
+    let db = Stats::read();
+    match format {
+        Export::Csv => print!("{}", to_csv(&db.history)),
+        Export::Json => match serde_json::to_string_pretty(&db.history) {
+            Ok(json) => println!("{json}"),
+            Err(e) => exit(format!("could not export: {e}")),
+        },
+    }
 }
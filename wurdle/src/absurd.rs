@@ -0,0 +1,157 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+use crate::{
+    game::{Board, GameStatus, GuessError, GuessFeedback, KeyState, WurdleGame},
+    solver::{pattern, Pattern},
+};
+
+/// Guesses you get in absurd mode by default, it takes a few more than usual.
+pub const ABSURD_TRIES: usize = 10;
+
+/// Splits `candidates` by the feedback `guess` would get if each of them were the answer.
+pub fn partition(guess: &str, candidates: &[String]) -> HashMap<Pattern, Vec<String>> {
+    let guess: Vec<char> = guess.chars().collect();
+    let mut buckets: HashMap<Pattern, Vec<String>> = HashMap::new();
+    for word in candidates {
+        let answer: Vec<char> = word.chars().collect();
+        if answer.len() != guess.len() {
+            continue;
+        }
+        buckets
+            .entry(pattern(&guess, &answer))
+            .or_default()
+            .push(word.clone());
+    }
+    buckets
+}
+
+// each digit is 2 for a green and 1 for a yellow, so a smaller sum gives less away
+fn digit_sum(mut pattern: Pattern) -> Pattern {
+    let mut sum = 0;
+    while pattern > 0 {
+        sum += pattern % 3;
+        pattern /= 3;
+    }
+    sum
+}
+
+/// The feedback that leaves the most words standing. Ties go to the one with the fewest greens
+/// and yellows, then to the lowest pattern so the choice never depends on hash order.
+pub fn worst_pattern(buckets: &HashMap<Pattern, Vec<String>>) -> Option<Pattern> {
+    buckets
+        .iter()
+        .max_by_key(|(p, words)| (words.len(), Reverse(digit_sum(**p)), Reverse(**p)))
+        .map(|(p, _)| *p)
+}
+
+/// Wurdle where the answer isn't picked up front. Every guess gets whichever feedback keeps the
+/// most words possible, so it's only won once there's nothing left to dodge with.
+pub struct AbsurdGame {
+    candidates: Vec<String>,
+    // played against whichever word is still standing
+    game: WurdleGame,
+}
+
+impl AbsurdGame {
+    pub fn new(candidates: &[String], tries: usize, allowed: HashSet<String>) -> Self {
+        let candidates: Vec<String> = candidates.iter().map(|w| w.to_uppercase()).collect();
+        let first = candidates.first().cloned().unwrap_or_default();
+        Self {
+            candidates,
+            game: WurdleGame::new(&first, tries, allowed),
+        }
+    }
+
+    pub fn guess(&mut self, input: &str) -> Result<GuessFeedback, GuessError> {
+        let candidates = &mut self.candidates;
+        self.game.guess_with(input, |guess| {
+            let mut buckets = partition(guess, candidates);
+            let worst = worst_pattern(&buckets)?;
+            *candidates = buckets.remove(&worst).unwrap_or_default();
+            // every word left gives the same feedback, so any of them will do
+            candidates.first().cloned()
+        })
+    }
+
+    /// Words that are still consistent with every piece of feedback given so far.
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.candidates.len()
+    }
+}
+
+impl Board for AbsurdGame {
+    fn attempts(&self) -> &[GuessFeedback] {
+        self.game.attempts()
+    }
+
+    fn tries(&self) -> usize {
+        self.game.tries()
+    }
+
+    fn word_len(&self) -> usize {
+        self.game.word_len()
+    }
+
+    fn status(&self) -> GameStatus {
+        self.game.status()
+    }
+
+    fn key_state(&self, c: char) -> KeyState {
+        self.game.key_state(c)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        absurd::{partition, worst_pattern, AbsurdGame},
+        game::{Board, GameStatus, GuessError},
+        solver::encode,
+    };
+
+    fn words() -> Vec<String> {
+        ["HELLO", "JELLO", "CELLO", "WORLD", "CRANE"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_biggest_bucket() {
+        let buckets = partition("HELLO", &words());
+        assert_eq!(buckets.len(), 4);
+        let worst = worst_pattern(&buckets).unwrap();
+        assert_eq!(buckets[&worst], vec!["JELLO", "CELLO"]);
+    }
+
+    #[test]
+    fn only_won_once_cornered() {
+        let allowed: HashSet<String> = HashSet::from_iter(words());
+        let mut game = AbsurdGame::new(&words(), 6, allowed);
+        assert!(matches!(game.guess("ZZZZZ"), Err(GuessError::NotAWord(_))));
+
+        let feedback = game.guess("HELLO").unwrap();
+        assert!(!feedback.is_correct());
+        assert_eq!(game.remaining(), 2);
+
+        // both are left with one word each, CELLO gives away less than a win would
+        let feedback = game.guess("JELLO").unwrap();
+        assert_eq!(
+            encode(&feedback.letters),
+            encode(&game.attempts()[0].letters)
+        );
+        assert_eq!(game.candidates(), ["CELLO"]);
+
+        game.guess("CELLO").unwrap();
+        assert_eq!(game.status(), GameStatus::Won { attempts: 3 });
+    }
+}
@@ -0,0 +1,49 @@
+use std::{fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::theme::Theme;
+
+const CONFIG_FILE: &str = "config.json";
+
+/// Preferences that stick between games, anything given on the command line wins over these.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    #[serde(default)]
+    pub theme: Option<Theme>,
+}
+
+pub fn default_path() -> PathBuf {
+    match dirs::config_dir() {
+        Some(dir) => dir.join("wurdle").join(CONFIG_FILE),
+        None => PathBuf::from("./.wurdle-config.json"),
+    }
+}
+
+impl Config {
+    /// A missing or unreadable config is the same as an empty one.
+    pub fn read() -> Self {
+        let path = default_path();
+        match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("ignoring {}: {e}", path.display());
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = default_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    /// The configured theme, falling back to what `NO_COLOR` asks for.
+    pub fn theme(&self) -> Theme {
+        self.theme.unwrap_or_else(Theme::from_env)
+    }
+}
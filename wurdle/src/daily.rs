@@ -0,0 +1,140 @@
+use std::fmt;
+
+use chrono::{NaiveDate, Utc};
+
+const SEED: u64 = 0x5755_5244_4c45; // "WURDLE"
+
+pub fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+}
+
+pub fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DailyError {
+    BeforeFirst(NaiveDate),
+    InFuture(NaiveDate),
+}
+
+impl fmt::Display for DailyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DailyError::BeforeFirst(date) => {
+                write!(
+                    f,
+                    "there was no daily wurdle on {date}, the first was on {}",
+                    epoch()
+                )
+            }
+            DailyError::InFuture(date) => write!(f, "the daily wurdle for {date} isn't out yet"),
+        }
+    }
+}
+
+impl std::error::Error for DailyError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Daily {
+    pub number: usize,
+    pub date: NaiveDate,
+    pub word: String,
+}
+
+/// The daily puzzle for `date`. Every run of `answers.len()` consecutive days walks through a
+/// seeded shuffle of the answers, so no word repeats until all of them have been used.
+pub fn daily(date: NaiveDate, answers: &[String]) -> Result<Daily, DailyError> {
+    if date > today() {
+        return Err(DailyError::InFuture(date));
+    }
+    let day = (date - epoch()).num_days();
+    if day < 0 || answers.is_empty() {
+        return Err(DailyError::BeforeFirst(date));
+    }
+    let day = day as usize;
+    let order = cycle_order(answers.len(), day / answers.len());
+
+    Ok(Daily {
+        number: day + 1,
+        date,
+        word: answers[order[day % answers.len()]].clone(),
+    })
+}
+
+fn cycle_order(len: usize, cycle: usize) -> Vec<usize> {
+    let mut order = shuffled(len, cycle);
+    // don't let the first day of a cycle repeat the last day of the one before
+    if cycle > 0 && len > 2 && shuffled(len, cycle - 1)[len - 1] == order[0] {
+        order.swap(0, 1);
+    }
+    order
+}
+
+fn shuffled(len: usize, cycle: usize) -> Vec<usize> {
+    // splitmix64, so the order only depends on this file and not on rand's internals
+    let mut state = SEED ^ (cycle as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+
+    let mut order: Vec<usize> = (0..len).collect();
+    for i in (1..len).rev() {
+        order.swap(i, (next() % (i as u64 + 1)) as usize);
+    }
+    order
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use chrono::{Duration, NaiveDate};
+
+    use crate::daily::{daily, epoch, DailyError};
+
+    fn words(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("W{i}")).collect()
+    }
+
+    #[test]
+    fn numbering() {
+        let answers = words(10);
+        assert_eq!(daily(epoch(), &answers).unwrap().number, 1);
+        assert_eq!(
+            daily(epoch() + Duration::days(41), &answers)
+                .unwrap()
+                .number,
+            42
+        );
+        let before = NaiveDate::from_ymd_opt(2024, 4, 30).unwrap();
+        assert_eq!(
+            daily(before, &answers),
+            Err(DailyError::BeforeFirst(before))
+        );
+    }
+
+    #[test]
+    fn unique_within_a_cycle() {
+        let answers = words(50);
+        let seen: HashSet<String> = (0..50)
+            .map(|day| daily(epoch() + Duration::days(day), &answers).unwrap().word)
+            .collect();
+        assert_eq!(seen.len(), 50);
+    }
+
+    #[test]
+    fn no_repeats_on_consecutive_days() {
+        let answers = words(7);
+        let mut last = String::new();
+        for day in 0..200 {
+            let word = daily(epoch() + Duration::days(day), &answers).unwrap().word;
+            assert_ne!(word, last);
+            last = word;
+        }
+    }
+}
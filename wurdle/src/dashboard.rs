@@ -0,0 +1,280 @@
+use std::{cmp::Reverse, collections::HashMap, fmt::Write};
+
+use chrono::NaiveDate;
+
+use crate::{
+    stats::{GameRecord, Summary},
+    theme::Theme,
+};
+
+// how many rows each list on the dashboard shows at most
+const TOP: usize = 5;
+const MONTHS: usize = 12;
+const BAR_WIDTH: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Month {
+    // YYYY-MM
+    pub month: String,
+    pub played: usize,
+    pub won: usize,
+    // guesses it took to win, summed over the games won
+    pub guesses: usize,
+}
+
+impl Month {
+    pub fn win_rate(&self) -> f64 {
+        self.won as f64 / self.played.max(1) as f64 * 100.0
+    }
+
+    pub fn average_guesses(&self) -> Option<f64> {
+        (self.won > 0).then(|| self.guesses as f64 / self.won as f64)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Streak {
+    pub length: usize,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+/// Everything the stats view shows beyond the [`Summary`], worked out from the game history.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dashboard {
+    // oldest first
+    pub months: Vec<Month>,
+    // longest first
+    pub streaks: Vec<Streak>,
+    // answers from lost games, most often missed first
+    pub missed: Vec<(String, usize)>,
+    pub first_guesses: Vec<(String, usize)>,
+}
+
+// most common first, ties alphabetical
+fn ranked(counts: HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    counts
+}
+
+impl Dashboard {
+    pub fn new<'a>(records: impl IntoIterator<Item = &'a GameRecord>) -> Self {
+        let mut months: Vec<Month> = vec![];
+        let mut streaks: Vec<Streak> = vec![];
+        let mut current: Option<Streak> = None;
+        let mut missed: HashMap<String, usize> = HashMap::new();
+        let mut first_guesses: HashMap<String, usize> = HashMap::new();
+
+        for record in records {
+            let month = record.date.format("%Y-%m").to_string();
+            if months.last().is_none_or(|m| m.month != month) {
+                months.push(Month {
+                    month,
+                    played: 0,
+                    won: 0,
+                    guesses: 0,
+                });
+            }
+            let month = months.last_mut().unwrap();
+            month.played += 1;
+
+            if record.won {
+                month.won += 1;
+                month.guesses += record.guesses.len();
+                let streak = current.get_or_insert(Streak {
+                    length: 0,
+                    from: record.date,
+                    to: record.date,
+                });
+                streak.length += 1;
+                streak.to = record.date;
+            } else {
+                streaks.extend(current.take());
+                for word in record.words() {
+                    if !record.guesses.iter().any(|g| g == word) {
+                        *missed.entry(word.to_string()).or_default() += 1;
+                    }
+                }
+            }
+            if let Some(first) = record.guesses.first() {
+                *first_guesses.entry(first.clone()).or_default() += 1;
+            }
+        }
+        streaks.extend(current);
+        // the sort is stable, so equal streaks stay oldest first, then the newest goes on top
+        streaks.reverse();
+        streaks.sort_by_key(|s| Reverse(s.length));
+
+        Self {
+            months,
+            streaks,
+            missed: ranked(missed),
+            first_guesses: ranked(first_guesses),
+        }
+    }
+
+    pub fn text(&self, summary: &Summary, theme: Theme) -> String {
+        let mut text = String::new();
+        let heading = |text: &mut String, title: &str| {
+            let _ = write!(text, "\n{title}\n{}\n", "=".repeat(title.len()));
+        };
+        if summary.played == 0 {
+            return "no games played yet\n".to_string();
+        }
+
+        let recent = &self.months[self.months.len().saturating_sub(MONTHS)..];
+        heading(&mut text, "WIN RATE BY MONTH");
+        for month in recent {
+            let rate = month.win_rate();
+            let bar = theme.bar(
+                (rate / 100.0 * BAR_WIDTH as f64).round() as usize,
+                &format!("{rate:.0}%"),
+                false,
+            );
+            let _ = writeln!(
+                text,
+                "{} {bar} ({}/{})",
+                month.month, month.won, month.played
+            );
+        }
+
+        heading(&mut text, "AVERAGE GUESSES BY MONTH");
+        let most = recent
+            .iter()
+            .filter_map(|m| m.average_guesses())
+            .fold(1.0, f64::max);
+        for month in recent {
+            match month.average_guesses() {
+                Some(average) => {
+                    let len = (average / most * BAR_WIDTH as f64).round() as usize;
+                    let bar = theme.bar(len, &format!("{average:.2}"), false);
+                    let _ = writeln!(text, "{} {bar}", month.month);
+                }
+                None => {
+                    let _ = writeln!(text, "{} -", month.month);
+                }
+            }
+        }
+
+        heading(&mut text, "STREAKS");
+        let _ = writeln!(
+            text,
+            "current {}, longest {}",
+            summary.streak, summary.maxstreak
+        );
+        for streak in self.streaks.iter().take(TOP) {
+            let _ = writeln!(
+                text,
+                "{:>3} {} to {}",
+                streak.length, streak.from, streak.to
+            );
+        }
+
+        for (title, words) in [
+            ("MOST MISSED", &self.missed),
+            ("FIRST GUESSES", &self.first_guesses),
+        ] {
+            if words.is_empty() {
+                continue;
+            }
+            heading(&mut text, title);
+            let most = words[0].1;
+            for (word, count) in words.iter().take(TOP) {
+                let bar = theme.bar(count * BAR_WIDTH / most, &count.to_string(), false);
+                let _ = writeln!(text, "{word:<8} {bar}");
+            }
+        }
+        text
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// The whole history, one game per line with a header line first.
+pub fn to_csv(records: &[GameRecord]) -> String {
+    let mut csv = String::from("date,word,guesses,won,mode,hard,length,dictionary,puzzle,boards\n");
+    for r in records {
+        let mode = serde_json::to_string(&r.mode).unwrap_or_default();
+        let fields = [
+            r.date.to_string(),
+            r.word.clone(),
+            r.guesses.join(" "),
+            r.won.to_string(),
+            mode.trim_matches('"').to_string(),
+            r.hard.to_string(),
+            r.length.to_string(),
+            r.dictionary.clone(),
+            r.puzzle.map(|p| p.to_string()).unwrap_or_default(),
+            r.boards.to_string(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv += &fields.join(",");
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::{
+        dashboard::{to_csv, Dashboard, Streak},
+        stats::{GameRecord, Mode},
+    };
+
+    fn game(date: &str, word: &str, guesses: &[&str], won: bool) -> GameRecord {
+        let guesses = guesses.iter().map(|g| g.to_string()).collect();
+        GameRecord {
+            date: date.parse().unwrap(),
+            ..GameRecord::new(word, guesses, won, Mode::Random)
+        }
+    }
+
+    #[test]
+    fn history() {
+        let records = vec![
+            game("2024-05-01", "HELLO", &["CRANE", "HELLO"], true),
+            game("2024-05-02", "WORLD", &["CRANE", "SLATE"], false),
+            game("2024-06-01", "HELLO", &["SLATE", "CRANE", "HELLO"], true),
+            game("2024-06-02", "ABOUT", &["CRANE", "ABOUT"], true),
+            game("2024-06-03", "WORLD", &["CRANE"], false),
+        ];
+        let dashboard = Dashboard::new(&records);
+
+        assert_eq!(dashboard.months.len(), 2);
+        assert_eq!(dashboard.months[0].win_rate(), 50.0);
+        assert_eq!(dashboard.months[1].average_guesses(), Some(2.5));
+        let date = |d: &str| d.parse::<NaiveDate>().unwrap();
+        assert_eq!(
+            dashboard.streaks,
+            vec![
+                Streak {
+                    length: 2,
+                    from: date("2024-06-01"),
+                    to: date("2024-06-02")
+                },
+                Streak {
+                    length: 1,
+                    from: date("2024-05-01"),
+                    to: date("2024-05-01")
+                },
+            ]
+        );
+        assert_eq!(dashboard.missed, vec![("WORLD".to_string(), 2)]);
+        assert_eq!(dashboard.first_guesses[0], ("CRANE".to_string(), 4));
+
+        let csv = to_csv(&records[..1]);
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("2024-05-01,HELLO,CRANE HELLO,true,random,false,5,default,,1")
+        );
+    }
+}
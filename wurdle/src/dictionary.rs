@@ -0,0 +1,151 @@
+use std::{collections::HashSet, fmt, fs, io, ops::RangeInclusive, path::Path};
+
+use crate::wurdle_words;
+
+pub const WORD_LENGTHS: RangeInclusive<usize> = 4..=8;
+pub const DEFAULT_DICTIONARY: &str = "default";
+
+#[derive(Debug)]
+pub enum DictionaryError {
+    Io(String, io::Error),
+    UnsupportedLength(usize),
+    NoWords { dictionary: String, length: usize },
+}
+
+impl fmt::Display for DictionaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DictionaryError::Io(path, e) => write!(f, "could not read {path}: {e}"),
+            DictionaryError::UnsupportedLength(length) => write!(
+                f,
+                "words must be {} to {} letters long, not {length}",
+                WORD_LENGTHS.start(),
+                WORD_LENGTHS.end()
+            ),
+            DictionaryError::NoWords { dictionary, length } => {
+                write!(f, "'{dictionary}' has no {length} letter words")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DictionaryError {}
+
+/// The words a game can pick its answer from, plus the (usually larger) set it will accept as
+/// guesses. Every word in a dictionary has the same length.
+#[derive(Clone, Debug)]
+pub struct Dictionary {
+    pub name: String,
+    pub length: usize,
+    pub answers: Vec<String>,
+    pub allowed: HashSet<String>,
+}
+
+impl Dictionary {
+    pub fn builtin(length: usize) -> Result<Self, DictionaryError> {
+        Self::from_words(
+            DEFAULT_DICTIONARY,
+            parse(wurdle_words::WURDLE_WURDS),
+            vec![],
+            length,
+        )
+    }
+
+    pub fn from_files(
+        answers: &Path,
+        guesses: Option<&Path>,
+        length: usize,
+    ) -> Result<Self, DictionaryError> {
+        let read = |path: &Path| {
+            fs::read_to_string(path)
+                .map(|s| parse(&s))
+                .map_err(|e| DictionaryError::Io(path.display().to_string(), e))
+        };
+        let name = answers
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| DEFAULT_DICTIONARY.to_string());
+        let guesses = match guesses {
+            Some(path) => read(path)?,
+            None => vec![],
+        };
+
+        Self::from_words(&name, read(answers)?, guesses, length)
+    }
+
+    /// Answers are always allowed as guesses, `guesses` only adds to them.
+    pub fn from_words(
+        name: &str,
+        answers: Vec<String>,
+        guesses: Vec<String>,
+        length: usize,
+    ) -> Result<Self, DictionaryError> {
+        if !WORD_LENGTHS.contains(&length) {
+            return Err(DictionaryError::UnsupportedLength(length));
+        }
+        let normalize = |words: Vec<String>| {
+            words
+                .into_iter()
+                .map(|w| w.trim().to_uppercase())
+                .filter(|w| w.chars().count() == length && w.chars().all(char::is_alphabetic))
+        };
+
+        let answers: Vec<String> = normalize(answers).collect();
+        if answers.is_empty() {
+            return Err(DictionaryError::NoWords {
+                dictionary: name.to_string(),
+                length,
+            });
+        }
+        let mut allowed: HashSet<String> = HashSet::from_iter(answers.clone());
+        allowed.extend(normalize(guesses));
+
+        Ok(Self {
+            name: name.to_string(),
+            length,
+            answers,
+            allowed,
+        })
+    }
+}
+
+// one word per line, `#` starts a comment line
+fn parse(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dictionary::{Dictionary, DictionaryError};
+
+    #[test]
+    fn lengths() {
+        let words = ["tree", "horse", "planet", "", "no way"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>();
+        let four = Dictionary::from_words("test", words.clone(), vec!["ABCD".into()], 4).unwrap();
+        assert_eq!(four.answers, vec!["TREE".to_string()]);
+        assert!(four.allowed.contains("ABCD"));
+
+        assert!(matches!(
+            Dictionary::from_words("test", words.clone(), vec![], 7),
+            Err(DictionaryError::NoWords { length: 7, .. })
+        ));
+        assert!(matches!(
+            Dictionary::from_words("test", words, vec![], 9),
+            Err(DictionaryError::UnsupportedLength(9))
+        ));
+    }
+
+    #[test]
+    fn builtin() {
+        let dictionary = Dictionary::builtin(5).unwrap();
+        assert!(dictionary.answers.iter().all(|w| w.len() == 5));
+        assert!(dictionary.allowed.contains("CRANE"));
+    }
+}
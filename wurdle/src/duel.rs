@@ -0,0 +1,675 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    io,
+    sync::mpsc,
+    time::Instant,
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use wobby::{
+    matchmaker::{Joined, Matchmaker},
+    net::{self, Event},
+};
+
+use crate::{
+    dictionary::Dictionary,
+    game::{GameStatus, KeyState, LetterResult, WurdleGame},
+    get_word, keyboard_text,
+    theme::{Theme, Tile},
+    tui::{self, Key, Terminal},
+};
+
+pub use wobby::net::Server;
+
+// one up from the wetris server
+pub const DEFAULT_PORT: u16 = 3043;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ClientMessage {
+    // wait in the lobby for an opponent, sent again after a match to play another
+    Join { name: String },
+    Guess { guess: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ServerMessage {
+    // everyone waiting for an opponent, you included
+    Lobby {
+        waiting: Vec<String>,
+    },
+    Start {
+        opponent: String,
+        length: usize,
+        tries: usize,
+    },
+    // your own guess, `time` is milliseconds since the match started
+    Feedback {
+        guess: String,
+        letters: Vec<LetterResult>,
+        time: u64,
+    },
+    Rejected {
+        reason: String,
+    },
+    // your opponent's guess, colours only
+    Opponent {
+        letters: Vec<LetterResult>,
+        time: u64,
+    },
+    // `winner` is None for a draw, `forfeit` if the loser left part way through
+    Finished {
+        winner: Option<String>,
+        word: String,
+        forfeit: bool,
+    },
+}
+
+struct Duel {
+    game: WurdleGame,
+    started: Instant,
+}
+
+/// Everything the server knows, with no network attached. `T` identifies a player, the server
+/// uses their endpoint. Each call returns the messages to send and who to send them to.
+pub struct Lobby<T> {
+    answers: Vec<String>,
+    allowed: HashSet<String>,
+    length: usize,
+    tries: usize,
+    rng: StdRng,
+    players: Matchmaker<T>,
+    duels: HashMap<T, Duel>,
+}
+
+impl<T: Copy + Eq + Hash> Lobby<T> {
+    pub fn new(dictionary: &Dictionary, tries: usize, rng: StdRng) -> Self {
+        Self {
+            answers: dictionary.answers.clone(),
+            allowed: dictionary.allowed.clone(),
+            length: dictionary.length,
+            tries,
+            rng,
+            players: Matchmaker::new(),
+            duels: HashMap::new(),
+        }
+    }
+
+    pub fn handle(
+        &mut self,
+        from: T,
+        message: ClientMessage,
+        now: Instant,
+    ) -> Vec<(T, ServerMessage)> {
+        match message {
+            ClientMessage::Join { name } => self.join(from, &name, now),
+            ClientMessage::Guess { guess } => self.guess(from, &guess, now),
+        }
+    }
+
+    fn join(&mut self, from: T, name: &str, now: Instant) -> Vec<(T, ServerMessage)> {
+        let (a, b) = match self.players.join(from, name) {
+            Joined::Playing => return vec![],
+            Joined::Waiting => return self.lobby_update(),
+            Joined::Paired(a, b) => (a, b),
+        };
+        let word = get_word(&mut self.rng, &self.answers);
+        let mut out = vec![];
+        for (me, opponent) in [(a, b), (b, a)] {
+            let game = WurdleGame::new(&word, self.tries, self.allowed.clone());
+            self.duels.insert(me, Duel { game, started: now });
+            out.push((
+                me,
+                ServerMessage::Start {
+                    opponent: self.players.name(opponent).to_string(),
+                    length: self.length,
+                    tries: self.tries,
+                },
+            ));
+        }
+        out.extend(self.lobby_update());
+        out
+    }
+
+    fn guess(&mut self, from: T, guess: &str, now: Instant) -> Vec<(T, ServerMessage)> {
+        let reject = |reason: String| vec![(from, ServerMessage::Rejected { reason })];
+        let (Some(opponent), Some(duel)) = (self.players.opponent(from), self.duels.get_mut(&from))
+        else {
+            return reject("you aren't in a match".to_string());
+        };
+        let feedback = match duel.game.guess(guess) {
+            Ok(feedback) => feedback,
+            Err(e) => return reject(e.to_string()),
+        };
+        let time = now.duration_since(duel.started).as_millis() as u64;
+        let out_of_tries = duel.game.status() == GameStatus::Lost;
+
+        let mut out = vec![
+            (
+                opponent,
+                ServerMessage::Opponent {
+                    letters: feedback.letters.clone(),
+                    time,
+                },
+            ),
+            (
+                from,
+                ServerMessage::Feedback {
+                    guess: feedback.guess.clone(),
+                    letters: feedback.letters.clone(),
+                    time,
+                },
+            ),
+        ];
+        let opponent_done = self.duels.get(&opponent).is_none_or(|d| d.game.is_over());
+        if feedback.is_correct() {
+            self.players.end(from);
+            out.extend(self.finish(from, opponent, Some(from), false));
+        } else if out_of_tries && opponent_done {
+            self.players.end(from);
+            out.extend(self.finish(from, opponent, None, false));
+        }
+        out
+    }
+
+    // tells both players how their match went, once it's been ended
+    fn finish(&mut self, a: T, b: T, winner: Option<T>, forfeit: bool) -> Vec<(T, ServerMessage)> {
+        let Some(duel) = self.duels.remove(&a) else {
+            return vec![];
+        };
+        self.duels.remove(&b);
+        let winner = winner.map(|w| self.players.name(w).to_string());
+        [a, b]
+            .into_iter()
+            .map(|to| {
+                (
+                    to,
+                    ServerMessage::Finished {
+                        winner: winner.clone(),
+                        word: duel.game.word().to_string(),
+                        forfeit,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// A player went away, whoever they were playing wins. If they'd already run out of guesses
+    /// there's nothing left for them to win, and it's a draw.
+    pub fn leave(&mut self, who: T) -> Vec<(T, ServerMessage)> {
+        let lost = |opponent: &T| {
+            self.duels
+                .get(opponent)
+                .is_some_and(|d| d.game.status() == GameStatus::Lost)
+        };
+        let mut out = match self.players.leave(who) {
+            Some(opponent) if lost(&opponent) => self.finish(opponent, who, None, false),
+            Some(opponent) => self.finish(opponent, who, Some(opponent), true),
+            None => vec![],
+        };
+        out.retain(|(to, _)| *to != who);
+        out.extend(self.lobby_update());
+        out
+    }
+
+    fn lobby_update(&self) -> Vec<(T, ServerMessage)> {
+        self.players
+            .waiting(|waiting| ServerMessage::Lobby { waiting })
+    }
+}
+
+/// Starts a server on `addr` that pairs up players as they arrive and referees their matches.
+pub fn serve(addr: &str, dictionary: &Dictionary, tries: usize) -> io::Result<Server> {
+    let mut lobby = Lobby::new(dictionary, tries, StdRng::from_entropy());
+    Server::start(addr, move |from, event| match event {
+        Event::Joined => vec![],
+        Event::Message(message) => lobby.handle(from, message, Instant::now()),
+        Event::Unreadable(e) => vec![(
+            from,
+            ServerMessage::Rejected {
+                reason: format!("could not read that: {e}"),
+            },
+        )],
+        Event::Left => lobby.leave(from),
+    })
+}
+
+pub type Connection = net::Connection<ClientMessage>;
+
+enum Phase {
+    Lobby(Vec<String>),
+    Playing,
+    Finished {
+        winner: Option<String>,
+        word: String,
+        forfeit: bool,
+    },
+}
+
+/// What the client has been told so far.
+struct View {
+    name: String,
+    opponent: String,
+    length: usize,
+    tries: usize,
+    mine: Vec<(String, Vec<LetterResult>, u64)>,
+    theirs: Vec<(Vec<LetterResult>, u64)>,
+    phase: Phase,
+}
+
+fn seconds(time: u64) -> String {
+    format!("{:>5.1}s", time as f64 / 1000.0)
+}
+
+impl View {
+    fn render(&self, typed: &str, notice: Option<&str>, theme: Theme) -> String {
+        let mut text = String::from("          wurdle duel\n==========================\n\n");
+        if let Phase::Lobby(waiting) = &self.phase {
+            text += "waiting for an opponent...\n\n";
+            if !waiting.is_empty() {
+                text += &format!("in the lobby: {}\n\n", waiting.join(", "));
+            }
+            text += "(esc) quit\n";
+            return text;
+        }
+
+        let blank = theme.tile('_', Tile::Plain);
+        let width = blank.chars().count() * self.length + 8;
+        text += &format!("    {:<width$}   {}\n", self.name, self.opponent);
+        for attempt in 0..self.tries {
+            let mine = match self.mine.get(attempt) {
+                Some((guess, letters, time)) => {
+                    let row: String = guess
+                        .chars()
+                        .zip(letters)
+                        .map(|(c, l)| theme.tile(c, (*l).into()))
+                        .collect();
+                    format!("{row} {}", seconds(*time))
+                }
+                None if attempt == self.mine.len() && self.playing() => {
+                    let row: String = typed.chars().map(|c| theme.tile(c, Tile::Plain)).collect();
+                    let left = self.length.saturating_sub(typed.chars().count());
+                    format!("{row}{}{}", blank.repeat(left), " ".repeat(7))
+                }
+                None => format!("{}{}", blank.repeat(self.length), " ".repeat(7)),
+            };
+            let theirs = match self.theirs.get(attempt) {
+                // just the colours, the letters are a secret
+                Some((letters, time)) => {
+                    let row: String = letters
+                        .iter()
+                        .map(|l| match l {
+                            LetterResult::Absent => theme.tile('·', Tile::Absent),
+                            _ => theme.tile(' ', (*l).into()),
+                        })
+                        .collect();
+                    format!("{row} {}", seconds(*time))
+                }
+                None => blank.repeat(self.length),
+            };
+            text += &format!("{:>2}) {mine}    {theirs}\n", attempt + 1);
+        }
+        text += "\n";
+        if let Some(notice) = notice {
+            text += &format!("{notice}\n\n");
+        }
+
+        match &self.phase {
+            Phase::Finished {
+                winner,
+                word,
+                forfeit,
+            } => {
+                // names can clash, so go by whose guesses solved it
+                let result = match winner {
+                    Some(_) if self.won() || *forfeit => "     you won!".to_string(),
+                    Some(winner) => format!("{winner} won!"),
+                    None => "it's a draw!".to_string(),
+                };
+                text += &format!("{result}\n");
+                if *forfeit {
+                    text += &format!("{} left the match\n", self.opponent);
+                }
+                text += &format!("the word was '{word}'\n");
+                for (who, guesses, time) in [
+                    ("you", self.mine.len(), self.mine.last().map(|m| m.2)),
+                    (
+                        self.opponent.as_str(),
+                        self.theirs.len(),
+                        self.theirs.last().map(|t| t.1),
+                    ),
+                ] {
+                    text += &format!(
+                        "{who}: {guesses} guess{} in {}\n",
+                        if guesses == 1 { "" } else { "es" },
+                        seconds(time.unwrap_or(0)).trim()
+                    );
+                }
+                text += "\n  play again? (y/n)\n";
+            }
+            _ => {
+                let mut keys: HashMap<char, KeyState> = HashMap::new();
+                for (guess, letters, _) in &self.mine {
+                    for (c, l) in guess.chars().zip(letters) {
+                        let key = keys.entry(c).or_insert(KeyState::Unused);
+                        *key = (*key).max((*l).into());
+                    }
+                }
+                text +=
+                    &keyboard_text(theme, |c| keys.get(&c).copied().unwrap_or(KeyState::Unused));
+                if !self.playing() && !self.won() {
+                    text += &format!("\nout of guesses, waiting for {}...\n", self.opponent);
+                }
+            }
+        }
+        text
+    }
+
+    fn won(&self) -> bool {
+        self.mine
+            .last()
+            .is_some_and(|(_, letters, _)| letters.iter().all(|l| *l == LetterResult::Correct))
+    }
+
+    // still has guesses to make
+    fn playing(&self) -> bool {
+        matches!(self.phase, Phase::Playing) && self.mine.len() < self.tries && !self.won()
+    }
+}
+
+enum Update {
+    Key(Key),
+    Server(Option<ServerMessage>),
+}
+
+/// Joins the server at `addr` as `name` and plays matches until the player quits.
+pub fn play_duel(addr: &str, name: &str, theme: Theme) -> io::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let server_tx = tx.clone();
+    let connection = Connection::connect(addr, move |message| {
+        let _ = server_tx.send(Update::Server(message));
+    })?;
+    let join = ClientMessage::Join {
+        name: name.to_string(),
+    };
+    connection.send(&join);
+
+    let mut term = Terminal::new()?;
+    let raw = term.is_raw();
+    tui::send_keys(raw, tx, Update::Key);
+
+    let mut view = View {
+        name: name.to_string(),
+        opponent: String::new(),
+        length: 0,
+        tries: 0,
+        mine: vec![],
+        theirs: vec![],
+        phase: Phase::Lobby(vec![]),
+    };
+    let mut typed = String::new();
+    let mut notice: Option<String> = None;
+
+    loop {
+        term.draw(&view.render(&typed, notice.as_deref(), theme))?;
+        let Ok(update) = rx.recv() else {
+            return Ok(());
+        };
+        match update {
+            Update::Server(None) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionAborted,
+                    "lost the connection to the server",
+                ))
+            }
+            Update::Server(Some(message)) => match message {
+                ServerMessage::Lobby { waiting } => {
+                    if let Phase::Lobby(_) = view.phase {
+                        view.phase = Phase::Lobby(waiting);
+                    }
+                }
+                ServerMessage::Start {
+                    opponent,
+                    length,
+                    tries,
+                } => {
+                    view = View {
+                        opponent,
+                        length,
+                        tries,
+                        mine: vec![],
+                        theirs: vec![],
+                        phase: Phase::Playing,
+                        ..view
+                    };
+                    typed.clear();
+                    notice = None;
+                }
+                ServerMessage::Feedback {
+                    guess,
+                    letters,
+                    time,
+                } => {
+                    view.mine.push((guess, letters, time));
+                    typed.clear();
+                    notice = None;
+                }
+                ServerMessage::Rejected { reason } => notice = Some(reason),
+                ServerMessage::Opponent { letters, time } => view.theirs.push((letters, time)),
+                ServerMessage::Finished {
+                    winner,
+                    word,
+                    forfeit,
+                } => {
+                    view.phase = Phase::Finished {
+                        winner,
+                        word,
+                        forfeit,
+                    };
+                    notice = None;
+                }
+            },
+            Update::Key(Key::Quit) => return Ok(()),
+            Update::Key(key) => match view.phase {
+                Phase::Lobby(_) => {}
+                Phase::Finished { .. } => {
+                    let answer = match key {
+                        Key::Char(c) => Some(c),
+                        Key::Line(ref line) => line.chars().next(),
+                        Key::Enter => Some('N'),
+                        _ => None,
+                    };
+                    match answer {
+                        Some('Y') => {
+                            view.phase = Phase::Lobby(vec![]);
+                            connection.send(&join);
+                        }
+                        Some('N') => return Ok(()),
+                        _ => {}
+                    }
+                }
+                Phase::Playing if !view.playing() => {}
+                Phase::Playing => match key {
+                    Key::Char(c) if typed.chars().count() < view.length => typed.push(c),
+                    Key::Backspace => {
+                        typed.pop();
+                    }
+                    Key::Enter if !typed.is_empty() => connection.send(&ClientMessage::Guess {
+                        guess: typed.clone(),
+                    }),
+                    Key::Line(guess) => connection.send(&ClientMessage::Guess { guess }),
+                    _ => {}
+                },
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::mpsc,
+        time::{Duration, Instant},
+    };
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        dictionary::Dictionary,
+        duel::{serve, ClientMessage, Connection, Lobby, ServerMessage},
+    };
+
+    fn dictionary() -> Dictionary {
+        Dictionary::from_words(
+            "test",
+            vec!["HELLO".into()],
+            vec!["CRANE".into(), "WORLD".into()],
+            5,
+        )
+        .unwrap()
+    }
+
+    fn join(name: &str) -> ClientMessage {
+        ClientMessage::Join {
+            name: name.to_string(),
+        }
+    }
+
+    fn guess(guess: &str) -> ClientMessage {
+        ClientMessage::Guess {
+            guess: guess.to_string(),
+        }
+    }
+
+    #[test]
+    fn first_to_solve_wins() {
+        let mut lobby = Lobby::new(&dictionary(), 6, StdRng::seed_from_u64(1));
+        let start = Instant::now();
+        assert_eq!(
+            lobby.handle(1, join("ann"), start),
+            vec![(
+                1,
+                ServerMessage::Lobby {
+                    waiting: vec!["ann".to_string()]
+                }
+            )]
+        );
+        let out = lobby.handle(2, join("bob"), start);
+        assert!(out.contains(&(
+            1,
+            ServerMessage::Start {
+                opponent: "bob".to_string(),
+                length: 5,
+                tries: 6
+            }
+        )));
+
+        let later = start + Duration::from_millis(1500);
+        let out = lobby.handle(2, guess("crane"), later);
+        assert!(matches!(
+            &out[0],
+            (1, ServerMessage::Opponent { time: 1500, .. })
+        ));
+        assert!(matches!(
+            lobby.handle(1, guess("zzzzz"), later)[0],
+            (1, ServerMessage::Rejected { .. })
+        ));
+
+        let out = lobby.handle(1, guess("hello"), later);
+        let finished = ServerMessage::Finished {
+            winner: Some("ann".to_string()),
+            word: "HELLO".to_string(),
+            forfeit: false,
+        };
+        assert!(out.contains(&(1, finished.clone())));
+        assert!(out.contains(&(2, finished)));
+        assert!(matches!(
+            lobby.handle(2, guess("hello"), later)[0],
+            (2, ServerMessage::Rejected { .. })
+        ));
+    }
+
+    #[test]
+    fn leaving_forfeits() {
+        let mut lobby = Lobby::new(&dictionary(), 6, StdRng::seed_from_u64(1));
+        let now = Instant::now();
+        lobby.handle(1, join("ann"), now);
+        lobby.handle(2, join("bob"), now);
+        assert_eq!(
+            lobby.leave(1),
+            vec![(
+                2,
+                ServerMessage::Finished {
+                    winner: Some("bob".to_string()),
+                    word: "HELLO".to_string(),
+                    forfeit: true,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn leaving_after_the_other_lost_is_a_draw() {
+        let mut lobby = Lobby::new(&dictionary(), 1, StdRng::seed_from_u64(1));
+        let now = Instant::now();
+        lobby.handle(1, join("ann"), now);
+        lobby.handle(2, join("bob"), now);
+        lobby.handle(2, guess("crane"), now);
+        assert_eq!(
+            lobby.leave(1),
+            vec![(
+                2,
+                ServerMessage::Finished {
+                    winner: None,
+                    word: "HELLO".to_string(),
+                    forfeit: false,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn over_localhost() {
+        let server = serve("127.0.0.1:0", &dictionary(), 2).unwrap();
+        let addr = server.addr().to_string();
+        let connect = || {
+            let (tx, rx) = mpsc::channel();
+            let connection = Connection::connect(&addr, move |m| {
+                let _ = tx.send(m);
+            })
+            .unwrap();
+            (connection, rx)
+        };
+        let (ann, ann_rx) = connect();
+        let (bob, bob_rx) = connect();
+        let next = |rx: &mpsc::Receiver<Option<ServerMessage>>| {
+            rx.recv_timeout(Duration::from_secs(5)).unwrap().unwrap()
+        };
+
+        ann.send(&join("ann"));
+        assert!(matches!(next(&ann_rx), ServerMessage::Lobby { .. }));
+        bob.send(&join("bob"));
+        assert!(matches!(next(&bob_rx), ServerMessage::Start { .. }));
+        assert!(matches!(next(&ann_rx), ServerMessage::Start { .. }));
+
+        for _ in 0..2 {
+            ann.send(&guess("world"));
+            assert!(matches!(next(&ann_rx), ServerMessage::Feedback { .. }));
+            bob.send(&guess("crane"));
+            assert!(matches!(next(&bob_rx), ServerMessage::Opponent { .. }));
+            assert!(matches!(next(&bob_rx), ServerMessage::Feedback { .. }));
+            assert!(matches!(next(&ann_rx), ServerMessage::Opponent { .. }));
+        }
+        assert_eq!(
+            next(&bob_rx),
+            ServerMessage::Finished {
+                winner: None,
+                word: "HELLO".to_string(),
+                forfeit: false
+            }
+        );
+    }
+}
@@ -0,0 +1,477 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LetterResult {
+    Correct,
+    Present,
+    Absent,
+}
+
+// ordered so a key only ever gets "upgraded" as more is revealed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyState {
+    Unused,
+    Absent,
+    Present,
+    Correct,
+}
+
+/// Scores `guess` against `answer` the way the real game does: greens first, then yellows, with
+/// each answer letter only able to light up one guess letter.
+pub fn evaluate<const N: usize>(guess: [char; N], answer: [char; N]) -> [LetterResult; N] {
+    let mut result = [LetterResult::Absent; N];
+    evaluate_into(&guess, &answer, &mut result);
+    result
+}
+
+pub fn evaluate_slice(guess: &[char], answer: &[char]) -> Vec<LetterResult> {
+    let mut result = vec![LetterResult::Absent; guess.len()];
+    evaluate_into(guess, answer, &mut result);
+    result
+}
+
+pub(crate) fn evaluate_into(guess: &[char], answer: &[char], result: &mut [LetterResult]) {
+    debug_assert!(guess.len() == answer.len() && guess.len() <= 64);
+    // bit i set = answer[i] is already accounted for
+    let mut spent = 0u64;
+    for i in 0..guess.len() {
+        if guess[i] == answer[i] {
+            result[i] = LetterResult::Correct;
+            spent |= 1 << i;
+        } else {
+            result[i] = LetterResult::Absent;
+        }
+    }
+    for i in 0..guess.len() {
+        if result[i] == LetterResult::Correct {
+            continue;
+        }
+        if let Some(j) = (0..answer.len()).find(|&j| spent & (1 << j) == 0 && answer[j] == guess[i])
+        {
+            result[i] = LetterResult::Present;
+            spent |= 1 << j;
+        }
+    }
+}
+
+impl From<LetterResult> for KeyState {
+    fn from(result: LetterResult) -> Self {
+        match result {
+            LetterResult::Correct => KeyState::Correct,
+            LetterResult::Present => KeyState::Present,
+            LetterResult::Absent => KeyState::Absent,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuessFeedback {
+    pub guess: String,
+    pub letters: Vec<LetterResult>,
+}
+
+impl GuessFeedback {
+    pub fn is_correct(&self) -> bool {
+        self.letters.iter().all(|l| *l == LetterResult::Correct)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (char, LetterResult)> + '_ {
+        self.guess.chars().zip(self.letters.iter().copied())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HardModeViolation {
+    MustKeep { position: usize, letter: char },
+    MustContain { letter: char, count: usize },
+}
+
+impl fmt::Display for HardModeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HardModeViolation::MustKeep { position, letter } => {
+                write!(f, "{} letter must be {letter}", ordinal(position + 1))
+            }
+            HardModeViolation::MustContain { letter, count: 1 } => {
+                write!(f, "guess must contain {letter}")
+            }
+            HardModeViolation::MustContain { letter, count } => {
+                write!(f, "guess must contain {count} {letter}s")
+            }
+        }
+    }
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuessError {
+    WrongLength { expected: usize, got: usize },
+    NotAWord(String),
+    HardMode(HardModeViolation),
+    GameOver,
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GuessError::WrongLength { expected, got } => {
+                write!(f, "guess has {got} letters, expected {expected}")
+            }
+            GuessError::NotAWord(word) => write!(f, "'{word}' is not a word! please try again"),
+            GuessError::HardMode(violation) => write!(f, "{violation}! please try again"),
+            GuessError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl std::error::Error for GuessError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    InProgress,
+    Won { attempts: usize },
+    Lost,
+}
+
+impl GameStatus {
+    pub fn is_over(&self) -> bool {
+        *self != GameStatus::InProgress
+    }
+}
+
+/// Uppercases `input` and makes sure it's an allowed word of the right length.
+fn check_guess(input: &str, len: usize, allowed: &HashSet<String>) -> Result<String, GuessError> {
+    let guess = input.trim().to_uppercase();
+    let got = guess.chars().count();
+    if got != len {
+        return Err(GuessError::WrongLength { expected: len, got });
+    }
+    if !allowed.contains(&guess) {
+        return Err(GuessError::NotAWord(guess));
+    }
+    Ok(guess)
+}
+
+/// What it takes to draw a game on screen, whether or not its answer is fixed.
+pub trait Board {
+    fn attempts(&self) -> &[GuessFeedback];
+    fn tries(&self) -> usize;
+    fn word_len(&self) -> usize;
+    fn status(&self) -> GameStatus;
+    fn key_state(&self, c: char) -> KeyState;
+
+    fn is_over(&self) -> bool {
+        self.status().is_over()
+    }
+}
+
+/// A single game of wurdle with no terminal attached. Guesses go in, feedback comes out.
+pub struct WurdleGame {
+    word: String,
+    word_vec: Vec<char>,
+    tries: usize,
+    allowed: HashSet<String>,
+    attempts: Vec<GuessFeedback>,
+    keyboard: HashMap<char, KeyState>,
+    status: GameStatus,
+    hard: bool,
+}
+
+impl WurdleGame {
+    pub fn new(word: &str, tries: usize, allowed: HashSet<String>) -> Self {
+        let word = word.to_uppercase();
+        let word_vec: Vec<char> = word.chars().collect();
+
+        Self {
+            word,
+            word_vec,
+            // a game with no guesses could never be lost
+            tries: tries.max(1),
+            allowed,
+            attempts: vec![],
+            keyboard: HashMap::new(),
+            status: GameStatus::InProgress,
+            hard: false,
+        }
+    }
+
+    /// In hard mode every revealed green has to stay put and every revealed yellow has to be
+    /// used again.
+    pub fn hard_mode(mut self, hard: bool) -> Self {
+        self.hard = hard;
+        self
+    }
+
+    pub fn is_hard(&self) -> bool {
+        self.hard
+    }
+
+    fn check_hard_mode(&self, guess: &[char]) -> Result<(), HardModeViolation> {
+        for attempt in &self.attempts {
+            for (position, (letter, result)) in attempt.iter().enumerate() {
+                if result == LetterResult::Correct && guess[position] != letter {
+                    return Err(HardModeViolation::MustKeep { position, letter });
+                }
+            }
+        }
+        for attempt in &self.attempts {
+            let mut required: Vec<(char, usize)> = vec![];
+            for (letter, result) in attempt.iter() {
+                if result == LetterResult::Absent {
+                    continue;
+                }
+                match required.iter_mut().find(|(c, _)| *c == letter) {
+                    Some((_, count)) => *count += 1,
+                    None => required.push((letter, 1)),
+                }
+            }
+            for (letter, count) in required {
+                if guess.iter().filter(|c| **c == letter).count() < count {
+                    return Err(HardModeViolation::MustContain { letter, count });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn guess(&mut self, input: &str) -> Result<GuessFeedback, GuessError> {
+        self.guess_with(input, |_| None)
+    }
+
+    /// Like [`guess`](Self::guess), but once the guess has passed every check `pick` can swap in a
+    /// different answer for it to be scored against.
+    pub(crate) fn guess_with(
+        &mut self,
+        input: &str,
+        pick: impl FnOnce(&str) -> Option<String>,
+    ) -> Result<GuessFeedback, GuessError> {
+        if self.status.is_over() {
+            return Err(GuessError::GameOver);
+        }
+        let guess = check_guess(input, self.word_vec.len(), &self.allowed)?;
+        let guess_vec: Vec<char> = guess.chars().collect();
+        if self.hard {
+            self.check_hard_mode(&guess_vec)
+                .map_err(GuessError::HardMode)?;
+        }
+        if let Some(word) = pick(&guess) {
+            self.word_vec = word.chars().collect();
+            self.word = word;
+        }
+
+        let letters = evaluate_slice(&guess_vec, &self.word_vec);
+        let feedback = GuessFeedback { guess, letters };
+
+        for (c, result) in feedback.iter() {
+            let key = self.keyboard.entry(c).or_insert(KeyState::Unused);
+            *key = (*key).max(result.into());
+        }
+
+        self.attempts.push(feedback.clone());
+        if feedback.is_correct() {
+            self.status = GameStatus::Won {
+                attempts: self.attempts.len(),
+            };
+        } else if self.attempts.len() >= self.tries {
+            self.status = GameStatus::Lost;
+        }
+
+        Ok(feedback)
+    }
+
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    pub fn word_len(&self) -> usize {
+        self.word_vec.len()
+    }
+
+    pub fn tries(&self) -> usize {
+        self.tries
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.tries.saturating_sub(self.attempts.len())
+    }
+
+    pub fn attempts(&self) -> &[GuessFeedback] {
+        &self.attempts
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.status.is_over()
+    }
+
+    pub fn key_state(&self, c: char) -> KeyState {
+        self.keyboard
+            .get(&c.to_ascii_uppercase())
+            .copied()
+            .unwrap_or(KeyState::Unused)
+    }
+}
+
+impl Board for WurdleGame {
+    fn attempts(&self) -> &[GuessFeedback] {
+        WurdleGame::attempts(self)
+    }
+
+    fn tries(&self) -> usize {
+        WurdleGame::tries(self)
+    }
+
+    fn word_len(&self) -> usize {
+        WurdleGame::word_len(self)
+    }
+
+    fn status(&self) -> GameStatus {
+        WurdleGame::status(self)
+    }
+
+    fn key_state(&self, c: char) -> KeyState {
+        WurdleGame::key_state(self, c)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::game::{
+        evaluate, GameStatus, GuessError, HardModeViolation, KeyState, LetterResult, WurdleGame,
+    };
+
+    fn game(word: &str, tries: usize) -> WurdleGame {
+        let allowed = HashSet::from_iter(
+            ["CRANE", "HELLO", "WORLD", "ABOUT", "SHELL"]
+                .iter()
+                .map(|s| s.to_string()),
+        );
+        WurdleGame::new(word, tries, allowed)
+    }
+
+    fn chars(word: &str) -> [char; 5] {
+        let v: Vec<char> = word.chars().collect();
+        v.try_into().unwrap()
+    }
+
+    #[test]
+    fn repeated_letters() {
+        use LetterResult::{Absent as A, Correct as C, Present as P};
+
+        let cases = [
+            ("HELLO", "HELLO", [C, C, C, C, C]),
+            ("LLAMA", "HELLO", [P, P, A, A, A]),
+            ("HELLO", "LLAMA", [A, A, P, P, A]),
+            ("SPEED", "ABIDE", [A, A, P, A, P]),
+            ("ERASE", "SPEED", [P, A, A, P, P]),
+            ("EERIE", "THEME", [P, A, A, A, C]),
+            ("ABBEY", "KEBAB", [P, P, C, P, A]),
+            ("BOBBY", "BLOBS", [C, P, A, C, A]),
+        ];
+        for (guess, answer, expected) in cases {
+            assert_eq!(
+                evaluate(chars(guess), chars(answer)),
+                expected,
+                "{guess} against {answer}"
+            );
+        }
+    }
+
+    #[test]
+    fn win() {
+        let mut g = game("hello", 6);
+        let feedback = g.guess("world").unwrap();
+        assert_eq!(feedback.letters[3], LetterResult::Correct);
+        assert_eq!(g.status(), GameStatus::InProgress);
+        assert!(g.guess("hello").unwrap().is_correct());
+        assert_eq!(g.status(), GameStatus::Won { attempts: 2 });
+        assert_eq!(g.guess("crane"), Err(GuessError::GameOver));
+    }
+
+    #[test]
+    fn lose() {
+        let mut g = game("hello", 2);
+        g.guess("crane").unwrap();
+        g.guess("about").unwrap();
+        assert_eq!(g.status(), GameStatus::Lost);
+
+        // zero tries still gets one guess, and can still be lost
+        let mut g = game("hello", 0);
+        g.guess("crane").unwrap();
+        assert_eq!(g.status(), GameStatus::Lost);
+        assert_eq!(g.remaining(), 0);
+    }
+
+    #[test]
+    fn rejected_guesses_do_not_count() {
+        let mut g = game("hello", 6);
+        assert_eq!(
+            g.guess("zzzzz"),
+            Err(GuessError::NotAWord("ZZZZZ".to_string()))
+        );
+        assert_eq!(
+            g.guess("hey"),
+            Err(GuessError::WrongLength {
+                expected: 5,
+                got: 3
+            })
+        );
+        assert_eq!(g.remaining(), 6);
+    }
+
+    #[test]
+    fn hard_mode() {
+        let mut g = game("hello", 6).hard_mode(true);
+        g.guess("world").unwrap();
+        let err = g.guess("about").unwrap_err();
+        assert_eq!(
+            err,
+            GuessError::HardMode(HardModeViolation::MustKeep {
+                position: 3,
+                letter: 'L'
+            })
+        );
+        assert_eq!(err.to_string(), "4th letter must be L! please try again");
+        assert_eq!(
+            g.guess("shell"),
+            Err(GuessError::HardMode(HardModeViolation::MustContain {
+                letter: 'O',
+                count: 1
+            }))
+        );
+        assert_eq!(g.attempts().len(), 1);
+        g.guess("hello").unwrap();
+        assert_eq!(g.status(), GameStatus::Won { attempts: 2 });
+    }
+
+    #[test]
+    fn keyboard() {
+        let mut g = game("hello", 6);
+        g.guess("world").unwrap();
+        assert_eq!(g.key_state('L'), KeyState::Correct);
+        assert_eq!(g.key_state('O'), KeyState::Present);
+        assert_eq!(g.key_state('W'), KeyState::Absent);
+        assert_eq!(g.key_state('Q'), KeyState::Unused);
+    }
+}
@@ -1,40 +1,269 @@
+pub mod absurd;
+pub mod config;
+pub mod daily;
+pub mod dashboard;
+pub mod dictionary;
+pub mod duel;
+pub mod game;
+pub mod multi;
+pub mod race;
+pub mod share;
+pub mod solver;
+pub mod stats;
+pub mod theme;
+pub mod tui;
 pub mod wurdle_words;
 
 use std::{
-    collections::{HashMap, HashSet},
-    env,
-    fs::{self, File, OpenOptions},
-    io::{self, stdin, Read, Write},
-    path::Path,
+    io,
+    path::PathBuf,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
 };
 
-use chrono::{Datelike, Utc};
-use rand::Rng;
+use chrono::NaiveDate;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-const COLORS: [(&str, &str); 4] = [
-    ("green", "\x1b[42m"),
-    ("yellow", "\x1b[43m"),
-    ("white", "\x1b[47m"),
-    ("reset", "\x1b[0m"),
-];
+use crate::{
+    absurd::AbsurdGame,
+    dictionary::{Dictionary, DEFAULT_DICTIONARY},
+    game::{Board, GameStatus, GuessError, KeyState, WurdleGame},
+    multi::{get_words, MultiGame},
+    solver::Solver,
+    stats::{GameRecord, Mode, Stats, Summary},
+    theme::{Theme, Tile},
+    tui::{Key, Terminal},
+};
+
+fn get_word(rng: &mut impl Rng, words_vec: &[String]) -> String {
+    words_vec[rng.gen::<usize>() % words_vec.len()].to_string()
+}
+
+/// The statistics block shown after a game, with the row for `won_attempt` highlighted.
+pub fn stats_text(
+    summary: &Summary,
+    tries: usize,
+    won_attempt: Option<usize>,
+    theme: Theme,
+) -> String {
+    let scale = 2;
+    let mut stats = format!(
+        "    STATISTICS
+==================
+Played:\t\t{}
+Win-rate:\t{}%
+Current Streak:\t{}
+Max Streak:\t{}
+
+GUESS DISTRIBUTION
+==================
+",
+        summary.played,
+        summary.win_rate(),
+        summary.streak,
+        summary.maxstreak,
+    );
+    for attempt in 1..=tries {
+        let count = summary.dist(attempt);
+        stats += &format!(
+            "{attempt} {}\n",
+            theme.bar(
+                count * scale,
+                &count.to_string(),
+                won_attempt == Some(attempt)
+            )
+        );
+    }
+    stats
+}
 
-fn colorize(input: String, color: &str, colors: &HashMap<&str, &str>) -> String {
-    format!("{}{input}\x1b[0m", colors[color])
+fn hint_text(solver: &Solver, game: &WurdleGame) -> String {
+    let left = solver.candidates(game.attempts()).len();
+    let hints = solver
+        .hints(game.attempts(), game.is_hard(), 3)
+        .iter()
+        .map(|hint| format!("{} ({:.2} bits)", hint.guess, hint.bits))
+        .collect::<Vec<String>>();
+    if hints.is_empty() {
+        return "hint: no words left that fit, the answer isn't in the dictionary".to_string();
+    }
+    format!(
+        "hint: try {} - {left} possible word{} left",
+        hints.join(", "),
+        if left == 1 { "" } else { "s" }
+    )
 }
 
-fn get_word(daily: bool, words_vec: &[String]) -> (String, Vec<char>, HashSet<char>) {
-    let word = words_vec[if daily {
-        let now = Utc::now();
-        ((now.year() as usize) << 2) | ((now.month() as usize) << 2) | (now.day() as usize)
+const KEYBOARD: [&str; 3] = ["QWERTYUIOP", "ASDFGHJKL", "ZXCVBNM"];
+// how far the row being typed moves on each frame when a guess is rejected
+const SHAKE: [usize; 6] = [2, 0, 2, 0, 1, 0];
+
+fn title_text(puzzle: Option<usize>, minion: Option<char>, hard: bool) -> String {
+    let mut title = String::from("          wurdle");
+    if let Some(puzzle) = puzzle {
+        title += &format!(" #{puzzle}");
+    }
+    if let Some(minion) = minion {
+        title += &format!(" ({minion})");
+    }
+    if hard {
+        title += " [hard]";
+    }
+    title
+}
+
+/// One row of a board: a guess that was made, `typed` if it's the row being filled in, or blank.
+fn row_text(
+    game: &impl Board,
+    attempt: usize,
+    typed: &str,
+    rejected: bool,
+    theme: Theme,
+) -> String {
+    let blank = |len: usize| theme.tile('_', Tile::Plain).repeat(len);
+    if let Some(feedback) = game.attempts().get(attempt) {
+        feedback
+            .iter()
+            .map(|(c, result)| theme.tile(c, result.into()))
+            .collect()
+    } else if attempt == game.attempts().len() && !game.is_over() {
+        let tile = if rejected { Tile::Invalid } else { Tile::Plain };
+        let mut row: String = typed.chars().map(|c| theme.tile(c, tile)).collect();
+        row += &blank(game.word_len().saturating_sub(typed.chars().count()));
+        row
     } else {
-        let mut rng = rand::thread_rng();
-        rng.gen::<usize>()
-    } % words_vec.len()]
-    .to_string();
-    let word_vec: Vec<char> = word.chars().collect();
-    let word_set: HashSet<char> = HashSet::from_iter(word_vec.clone());
-
-    (word, word_vec, word_set)
+        blank(game.word_len())
+    }
+}
+
+fn keyboard_text(theme: Theme, key_state: impl Fn(char) -> KeyState) -> String {
+    let mut text = String::new();
+    let indent = (theme.tile(' ', Tile::Plain).chars().count() + theme.key_gap().len()) / 2;
+    for (i, row) in KEYBOARD.iter().enumerate() {
+        let keys: Vec<String> = row.chars().map(|c| theme.key(c, key_state(c))).collect();
+        text += &format!("{}{}\n", " ".repeat(i * indent), keys.join(theme.key_gap()));
+    }
+    text
+}
+
+const HELP: &str = "(enter) guess  (backspace) delete  (?) hint  (esc) quit\n";
+
+/// The board with `typed` filling the current row. `shake` moves that row over and flashes it,
+/// for guesses that were rejected.
+fn board_text(
+    title: &str,
+    game: &impl Board,
+    typed: &str,
+    shake: Option<usize>,
+    notice: Option<&str>,
+    theme: Theme,
+) -> String {
+    let mut text = format!("{title}\n==========================\n\n");
+
+    for attempt in 0..game.tries() {
+        text += &format!("{}) ", attempt + 1);
+        if attempt == game.attempts().len() {
+            text += &" ".repeat(shake.unwrap_or(0));
+        }
+        text += &row_text(game, attempt, typed, shake.is_some(), theme);
+        text += "\n\n";
+    }
+    if let Some(notice) = notice {
+        text += &format!("{notice}\n\n");
+    }
+    text += &keyboard_text(theme, |c| game.key_state(c));
+    text += "\n";
+    if !game.is_over() {
+        text += HELP;
+    }
+    text
+}
+
+// boards side by side, any more than this start a new band underneath
+const BOARDS_ACROSS: usize = 4;
+
+fn boards_text(
+    title: &str,
+    game: &MultiGame,
+    typed: &str,
+    shake: Option<usize>,
+    notice: Option<&str>,
+    theme: Theme,
+) -> String {
+    let mut text = format!(
+        "{title}\n==========================\n{}/{} solved, {} guesses left\n\n",
+        game.solved(),
+        game.boards().len(),
+        game.tries() - game.guesses().len()
+    );
+
+    for band in game.boards().chunks(BOARDS_ACROSS) {
+        for attempt in 0..game.tries() {
+            text += &format!("{:>2}) ", attempt + 1);
+            if attempt == game.guesses().len() {
+                text += &" ".repeat(shake.unwrap_or(0));
+            }
+            let rows: Vec<String> = band
+                .iter()
+                .map(|board| match board.status() {
+                    // solved boards stay empty below the answer
+                    GameStatus::Won { attempts } if attempt >= attempts => {
+                        " ".repeat(theme.tile('_', Tile::Plain).chars().count() * board.word_len())
+                    }
+                    _ => row_text(board, attempt, typed, shake.is_some(), theme),
+                })
+                .collect();
+            text += &rows.join("   ");
+            text += "\n";
+        }
+        text += "\n";
+    }
+    if let Some(notice) = notice {
+        text += &format!("{notice}\n\n");
+    }
+    text += &keyboard_text(theme, |c| game.key_state(c));
+    text += "\n";
+    if !game.is_over() {
+        text += HELP;
+    }
+    text
+}
+
+#[derive(Clone, Debug)]
+pub struct PlayOptions {
+    pub tries: usize,
+    pub minion: Option<char>,
+    pub show_stats: bool,
+    pub hard: bool,
+    // play the daily puzzle for this date instead of a random word
+    pub daily: Option<NaiveDate>,
+    // also write the share block here when a game ends
+    pub share_file: Option<PathBuf>,
+    // makes the sequence of random words reproducible
+    pub seed: Option<u64>,
+    pub theme: Theme,
+    // how many words are guessed at once, each on its own board
+    pub boards: usize,
+    // the answer dodges every guess for as long as it can
+    pub absurd: bool,
+}
+
+impl Default for PlayOptions {
+    fn default() -> Self {
+        Self {
+            tries: 6,
+            minion: None,
+            show_stats: false,
+            hard: false,
+            daily: None,
+            share_file: None,
+            seed: None,
+            theme: Theme::default(),
+            boards: 1,
+            absurd: false,
+        }
+    }
 }
 
 pub fn play(
@@ -44,28 +273,157 @@ pub fn play(
     minion: Option<char>,
     show_stats: bool,
 ) -> (bool, Vec<String>, String) {
-    print!("{}[2J", 27 as char);
+    let dictionary = Dictionary::from_words(DEFAULT_DICTIONARY, all_words_vec, vec![], 5)
+        .expect("the word list should have five letter words");
+    play_with(
+        &dictionary,
+        &PlayOptions {
+            tries,
+            minion,
+            show_stats,
+            ..Default::default()
+        },
+    )
+}
 
-    print!("\x1B[2J\x1B[1;1H");
-    // stdin().read_to_end(&mut vec![]).unwrap();
+/// The parts of a game that change from one way of playing to another. [`play_loop`] does the
+/// typing, the guessing and the restart prompt around them.
+trait Session {
+    fn word_len(&self) -> usize;
+    fn is_over(&self) -> bool;
+    /// The whole screen, with `typed` in the row being filled in and `shake` moving it over.
+    fn frame(&self, typed: &str, shake: Option<usize>, notice: Option<&str>) -> String;
+    /// Whatever comes back is shown as a notice, like stats that couldn't be saved.
+    fn guess(&mut self, input: &str) -> Result<Option<String>, GuessError>;
+    /// Called with "thinking..." already on screen.
+    fn hint(&mut self) -> String;
+    fn restart(&mut self);
 
-    let debug = false;
-    let words_set: HashSet<String> = HashSet::from_iter(all_words_vec.clone());
+    /// How long to wait for a key before redrawing anyway, for games with a clock.
+    fn refresh(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Called whenever `refresh` goes by with no key pressed.
+    fn tick(&mut self) {}
+}
 
-    // let args = env::args().collect::<Vec<String>>();
-    // let minion: Option<char> = None;
+/// Reads keys and draws frames until the player quits, or says no to another game.
+fn play_loop(term: &mut Terminal, session: &mut impl Session) -> io::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    tui::send_keys(term.is_raw(), tx, |key| key);
+    let mut typed = String::new();
+    let mut notice: Option<String> = None;
 
-    let choice_words: Vec<String> = all_words_vec
-        .clone()
+    loop {
+        let frame = session.frame(&typed, None, notice.take().as_deref());
+        term.draw(&frame)?;
+
+        let key = match session.refresh() {
+            Some(refresh) => match rx.recv_timeout(refresh) {
+                Ok(key) => key,
+                Err(RecvTimeoutError::Timeout) => {
+                    session.tick();
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => Key::Quit,
+            },
+            None => rx.recv().unwrap_or(Key::Quit),
+        };
+        if session.is_over() {
+            let answer = match key {
+                Key::Char(c) => Some(c),
+                Key::Line(ref line) => line.chars().next(),
+                Key::Enter | Key::Quit => Some('N'),
+                _ => None,
+            };
+            match answer {
+                Some('Y') => session.restart(),
+                Some('N') => return Ok(()),
+                _ => {}
+            }
+            continue;
+        }
+        match key {
+            Key::Char(c) if typed.chars().count() < session.word_len() => typed.push(c),
+            Key::Char(_) => {}
+            Key::Backspace => {
+                typed.pop();
+            }
+            Key::Hint => {
+                term.draw(&session.frame(&typed, None, Some("thinking...")))?;
+                notice = Some(session.hint());
+            }
+            Key::Quit => return Ok(()),
+            Key::Enter | Key::Line(_) => {
+                if let Key::Line(line) = key {
+                    typed = line;
+                }
+                if typed.is_empty() {
+                    continue;
+                }
+                match session.guess(&typed) {
+                    Ok(result) => {
+                        typed.clear();
+                        notice = result;
+                    }
+                    Err(e) => {
+                        if term.is_raw() {
+                            for offset in SHAKE {
+                                term.draw(&session.frame(&typed, Some(offset), None))?;
+                                thread::sleep(Duration::from_millis(40));
+                            }
+                        } else {
+                            typed.clear();
+                        }
+                        notice = Some(e.to_string());
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn new_terminal() -> Option<Terminal> {
+    Terminal::new()
+        .map_err(|e| eprintln!("could not set up the terminal: {e}"))
+        .ok()
+}
+
+/// Plays `session` to the end, restoring the terminal before any error is reported.
+fn run_session(mut term: Terminal, session: &mut impl Session) -> bool {
+    let result = play_loop(&mut term, session);
+    drop(term);
+    result
+        .map_err(|e| eprintln!("the terminal stopped working: {e}"))
+        .is_ok()
+}
+
+pub fn play_with(dictionary: &Dictionary, options: &PlayOptions) -> (bool, Vec<String>, String) {
+    let PlayOptions {
+        tries,
+        minion,
+        hard,
+        daily,
+        seed,
+        boards,
+        absurd,
+        ..
+    } = *options;
+    if absurd {
+        return play_absurd(dictionary, options);
+    }
+    if boards > 1 {
+        return play_boards(dictionary, options);
+    }
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let mut choice_words: Vec<String> = dictionary
+        .answers
         .iter()
-        // .filter(|word| {
-        //     if let Some(available_letters) = available_letters) &&! available_letters.is_empty()  {
-        //         word.chars().all(|c| available_letters.contains(&c))
-        //     } else {
-        //         true
-        //     }
-        // })
-        .map(|s| s.to_string())
         .filter(|s| {
             if let Some(minion) = minion {
                 s.contains(minion)
@@ -73,459 +431,398 @@ pub fn play(
                 true
             }
         })
+        .cloned()
         .collect();
-    // if choice_words.is_empty() {
-    //     choice_words = all_words_vec
-    //         .clone()
-    //         .iter()
-    //         .map(|s| s.to_string())
-    //         .filter(|s| {
-    //             if let Some(minion) = minion {
-    //                 s.contains(minion)
-    //             } else {
-    //                 true
-    //             }
-    //         })
-    //         .collect()
-    // }
-    let (mut word, mut word_vec, mut word_set) = get_word(
-        if let Some(arg) = env::args().nth(1) {
-            arg == "daily"
-        } else {
-            false
-        },
-        &choice_words,
-    );
+    if choice_words.is_empty() {
+        choice_words = dictionary.answers.clone();
+    }
+    let db = Stats::read();
+    let (word, mode, puzzle) = match daily {
+        Some(date) => {
+            let daily = match daily::daily(date, &dictionary.answers) {
+                Ok(daily) => daily,
+                Err(e) => {
+                    eprintln!("{e}");
+                    return (false, vec![], String::new());
+                }
+            };
+            let mode = if date == daily::today() {
+                Mode::Daily
+            } else {
+                Mode::Replay
+            };
+            if let Some(record) = db.daily_played(daily.number, dictionary.length, &dictionary.name)
+            {
+                if mode == Mode::Daily {
+                    println!(
+                        "you already played wurdle #{} today, come back tomorrow!",
+                        daily.number
+                    );
+                    let attempts = share::feedback_for(&record.word, &record.guesses);
+                    println!(
+                        "\n{}\n",
+                        share::share_text(
+                            Some(daily.number),
+                            &attempts,
+                            record.won,
+                            tries,
+                            record.hard
+                        )
+                    );
+                    return (record.won, record.guesses.clone(), record.word.clone());
+                }
+            }
+            (daily.word, mode, Some(daily.number))
+        }
+        None => (get_word(&mut rng, &choice_words), Mode::Random, None),
+    };
 
-    let mut db = Stats::read();
-    let stdin = io::stdin();
-    let mut input = String::new();
-    println!("\x1B[2J\x1B[1;1H");
-    let mut attempts: Vec<String> = vec![];
-    let colors: HashMap<&str, &str> = HashMap::from_iter(COLORS);
-    let mut game_over = false;
-    const LETTERS: &str = "Q W E R T Y U I O P\n A S D F G H J K L\n  Z X C V B N M";
-    let mut used_letters: HashSet<char> = HashSet::new();
-    let mut misplaced_letters: HashSet<char> = HashSet::new();
-    let mut correct_letters: HashSet<char> = HashSet::new();
-    let mut not_a_word = false;
-    let mut game_over_text: Option<String> = None;
-    let mut won_attempt: Option<usize> = None;
-    let mut won = false;
+    let Some(term) = new_terminal() else {
+        return (false, vec![], String::new());
+    };
+    let mut session = SingleSession {
+        dictionary,
+        options,
+        debug: false,
+        rng,
+        choice_words,
+        db,
+        game: WurdleGame::new(&word, tries, dictionary.allowed.clone()).hard_mode(hard),
+        mode,
+        puzzle,
+        solver: None,
+    };
+    if !run_session(term, &mut session) {
+        return (false, vec![], String::new());
+    }
 
-    loop {
-        print!("\x1B[2J\x1B[1;1H");
-        print!("          wurdle");
-        if let Some(minion) = minion {
-            print!(" ({})", minion);
-        }
-        println!("\n==========================\n");
-        if debug {
-            println!("{}", &word);
-        }
+    let game = &session.game;
+    let attempts = game.attempts().iter().map(|a| a.guess.clone()).collect();
+    let won = matches!(game.status(), GameStatus::Won { .. });
+    (won, attempts, game.word().to_string())
+}
 
-        if !attempts.is_empty() && !words_set.contains(attempts.last().unwrap()) {
-            attempts.pop();
-            not_a_word = true;
-        }
+// one board, a random word or the daily
+struct SingleSession<'a> {
+    dictionary: &'a Dictionary,
+    options: &'a PlayOptions,
+    // shows the answer under the title
+    debug: bool,
+    rng: StdRng,
+    choice_words: Vec<String>,
+    db: Stats,
+    game: WurdleGame,
+    mode: Mode,
+    puzzle: Option<usize>,
+    solver: Option<Solver>,
+}
 
-        for attempt in 0..tries {
-            print!("{}) ", attempt + 1);
+impl Session for SingleSession<'_> {
+    fn word_len(&self) -> usize {
+        self.game.word_len()
+    }
 
-            if attempt < attempts.len() {
-                let a = &attempts[attempt];
-                'inner: for (i, c) in a.chars().enumerate() {
-                    if i >= word_vec.len() {
-                        break 'inner;
-                    }
-                    if c == (&word_vec)[i] {
-                        print!("{}", colorize(c.into(), "green", &colors));
-                        correct_letters.insert(c);
-                    } else if word_set.contains(&c) {
-                        print!("{}", colorize(c.into(), "yellow", &colors));
-                        misplaced_letters.insert(c);
-                    } else {
-                        print!("{}", colorize(c.into(), "reset", &colors));
-                        used_letters.insert(c);
-                    }
-                }
-                print!("\n\n");
-                if *a == word {
-                    game_over_text = Some("     you won!".to_string());
-                    game_over = true;
-                    db.won(attempt + 1);
-                    db.save();
-                    won_attempt = Some(attempt + 1);
-                    won = true;
-                } else if attempt + 1 == tries {
-                    game_over_text = Some(format!("you lost! the word was '{word}'"));
-                    game_over = true;
-                    won_attempt = None;
-                    won = false;
-                    db.save();
-                    db.lost();
-                }
-            } else {
-                println!("_____\n");
-            }
-        }
-        if not_a_word {
-            println!("'{input}' is not a word! please try again\n");
-            not_a_word = false;
-        }
-        for c in LETTERS.chars() {
-            print!(
-                "{}",
-                colorize(
-                    c.into(),
-                    if correct_letters.contains(&c) {
-                        "green"
-                    } else if misplaced_letters.contains(&c) {
-                        "yellow"
-                    } else if used_letters.contains(&c)
-                        || c == ' '
-                        // || (!available_letters.is_empty() && !available_letters.contains(&c))
-                    {
-                        "reset"
-                    } else {
-                        "white"
-                    },
-                    &colors
-                )
-            )
+    fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    fn frame(&self, typed: &str, shake: Option<usize>, notice: Option<&str>) -> String {
+        let PlayOptions {
+            tries,
+            minion,
+            show_stats,
+            hard,
+            theme,
+            ..
+        } = *self.options;
+        let (dictionary, game) = (self.dictionary, &self.game);
+        let mut title = title_text(self.puzzle, minion, hard);
+        if self.debug {
+            title += &format!("\n{}", game.word());
         }
-        println!("\n");
+        let mut frame = board_text(&title, game, typed, shake, notice, theme);
+        let won_attempt = match game.status() {
+            GameStatus::Won { attempts } => Some(attempts),
+            _ => None,
+        };
+        let game_over_text = match game.status() {
+            GameStatus::InProgress => None,
+            GameStatus::Won { .. } => Some("     you won!".to_string()),
+            GameStatus::Lost => Some(format!("you lost! the word was '{}'", game.word())),
+        };
         if let Some(ref text) = game_over_text {
-            let scale = 2;
-            let mut stats = format!(
-                "    STATISTICS
-==================
-Played:\t\t{}
-Win-rate:\t{}%
-Current Streak:\t{}
-Max Streak:\t{}
-
-GUESS DISTRIBUTION
-==================
-1 {}{}
-2 {}{}
-3 {}{}
-4 {}{}
-5 {}{}
-6 {}{}\n\n  restart? (y/n)",
-                db.played,
-                f64::trunc((db.won as f64) / (db.played as f64) * 100.0 * 100.0) / 100.0,
-                db.streak,
-                db.maxstreak,
-                colorize(
-                    ' '.into(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 1 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                )
-                .repeat(db.dist_1 * scale),
-                colorize(
-                    db.dist_1.to_string(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 1 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                ),
-                colorize(
-                    ' '.into(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 2 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                )
-                .repeat(db.dist_2 * scale),
-                colorize(
-                    db.dist_2.to_string(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 2 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                ),
-                colorize(
-                    ' '.into(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 3 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                )
-                .repeat(db.dist_3 * scale),
-                colorize(
-                    db.dist_3.to_string(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 3 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                ),
-                colorize(
-                    ' '.into(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 4 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                )
-                .repeat(db.dist_4 * scale),
-                colorize(
-                    db.dist_4.to_string(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 4 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                ),
-                colorize(
-                    ' '.into(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 5 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                )
-                .repeat(db.dist_5 * scale),
-                colorize(
-                    db.dist_5.to_string(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 5 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                ),
-                colorize(
-                    ' '.into(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 6 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                )
-                .repeat(db.dist_6 * scale),
-                colorize(
-                    db.dist_6.to_string(),
-                    if let Some(won_attempt) = won_attempt {
-                        if won_attempt == 6 {
-                            "green"
-                        } else {
-                            "white"
-                        }
-                    } else {
-                        "white"
-                    },
-                    &colors
-                ),
-            );
+            let summary = self.db.summary_for(dictionary.length, &dictionary.name);
+            let mut stats = stats_text(&summary, tries, won_attempt, theme) + "\n  restart? (y/n)";
             if !show_stats {
                 stats = "press (enter) to continue".to_string();
             }
-            println!("\n{text}\n\n{stats}\n");
+            let share = share::share_text(
+                self.puzzle,
+                game.attempts(),
+                won_attempt.is_some(),
+                tries,
+                hard,
+            );
+            frame += &format!("\n{text}\n\n{share}\n\n{stats}\n");
         }
-        print!("> ");
-        io::stdout().flush().unwrap();
-
-        input.clear();
-        stdin.read_line(&mut input).unwrap();
-        input.pop();
-        // input.retain(|c| c.is_alphabetic());
-
-        // let bytes = input.as_bytes();
-        // if bytes.len() > 3 && &bytes[0..2] == &[27, 91] {
-        //     input = String::from_utf8_lossy(&bytes[3..]).to_string();
-        // }
-
-        if game_over {
-            if input == "y" {
-                game_over = false;
-                (word, word_vec, word_set) = get_word(false, &all_words_vec);
-                attempts.clear();
-                used_letters.clear();
-                misplaced_letters.clear();
-                correct_letters.clear();
-                game_over_text = None;
-            } else if input == "n" || input.trim().is_empty() {
-                return (won, attempts, word);
-            }
+        frame
+    }
+
+    fn guess(&mut self, input: &str) -> Result<Option<String>, GuessError> {
+        let PlayOptions {
+            tries,
+            hard,
+            ref share_file,
+            ..
+        } = *self.options;
+        let game = &mut self.game;
+        game.guess(input)?;
+        // today's daily is saved from the first guess on, so it can't be quit and started again
+        let daily = self.mode == Mode::Daily;
+        if !game.is_over() && !daily {
+            return Ok(None);
+        }
+        let mut notice = None;
+        let guesses = game.attempts().iter().map(|a| a.guess.clone()).collect();
+        let won = matches!(game.status(), GameStatus::Won { .. });
+        let record = GameRecord::new(game.word(), guesses, won, self.mode)
+            .hard(hard)
+            .dictionary(&self.dictionary.name)
+            .puzzle(self.puzzle);
+        if daily {
+            self.db.record_daily(record);
         } else {
-            if input.len() > 5 {
-                input = input[input.len() - 5..].to_string();
-            }
-            if input != "" {
-                attempts.push(input.to_uppercase());
+            self.db.record(record);
+        }
+        if let Err(e) = self.db.save() {
+            notice = Some(format!("could not save stats: {e}"));
+        }
+        if !game.is_over() {
+            return Ok(notice);
+        }
+        if let Some(path) = share_file {
+            let share = share::share_text(self.puzzle, game.attempts(), won, tries, hard);
+            if let Err(e) = share::write_share(path, &share) {
+                notice = Some(format!("could not write {}: {e}", path.display()));
             }
-
         }
+        Ok(notice)
+    }
+
+    fn hint(&mut self) -> String {
+        let dictionary = self.dictionary;
+        let solver = self.solver.get_or_insert_with(|| Solver::new(dictionary));
+        hint_text(solver, &self.game)
+    }
+
+    fn restart(&mut self) {
+        let PlayOptions { tries, hard, .. } = *self.options;
+        let word = get_word(&mut self.rng, &self.choice_words);
+        self.game = WurdleGame::new(&word, tries, self.dictionary.allowed.clone()).hard_mode(hard);
+        self.mode = Mode::Random;
+        self.puzzle = None;
     }
 }
-struct Stats {
-    pub played: usize,
-    pub won: usize,
-    pub streak: usize,
-    pub maxstreak: usize,
-    pub dist_1: usize,
-    pub dist_2: usize,
-    pub dist_3: usize,
-    pub dist_4: usize,
-    pub dist_5: usize,
-    pub dist_6: usize,
+
+/// Every guess goes to all `options.boards` boards at once. Hard mode, dailies and the minion
+/// letter only apply to single board games.
+fn play_boards(dictionary: &Dictionary, options: &PlayOptions) -> (bool, Vec<String>, String) {
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let words = get_words(&mut rng, &dictionary.answers, options.boards);
+    let game = MultiGame::new(&words, options.tries, dictionary.allowed.clone());
+    let Some(term) = new_terminal() else {
+        return (false, vec![], String::new());
+    };
+    let mut session = MultiSession {
+        dictionary,
+        options,
+        rng,
+        db: Stats::read(),
+        game,
+    };
+    if !run_session(term, &mut session) {
+        return (false, vec![], String::new());
+    }
+
+    let game = &session.game;
+    let won = matches!(game.status(), GameStatus::Won { .. });
+    (won, game.guesses().to_vec(), game.words().join(" "))
 }
 
-const DB_FILE: &str = "./.wurdle";
-
-impl Stats {
-    pub fn read() -> Self {
-        let lines = fs::read_to_string(DB_FILE);
-
-        if let Ok(lines) = lines {
-            let lines = lines.split('\n').collect::<Vec<&str>>();
-            Self {
-                played: lines[0].parse().unwrap(),
-                won: lines[1].parse().unwrap(),
-                streak: lines[2].parse().unwrap(),
-                maxstreak: lines[3].parse().unwrap(),
-                dist_1: lines[4].parse().unwrap(),
-                dist_2: lines[5].parse().unwrap(),
-                dist_3: lines[6].parse().unwrap(),
-                dist_4: lines[7].parse().unwrap(),
-                dist_5: lines[8].parse().unwrap(),
-                dist_6: lines[9].parse().unwrap(),
-            }
-        } else {
-            Self {
-                played: 0,
-                won: 0,
-                streak: 0,
-                maxstreak: 0,
-                dist_1: 0,
-                dist_2: 0,
-                dist_3: 0,
-                dist_4: 0,
-                dist_5: 0,
-                dist_6: 0,
+struct MultiSession<'a> {
+    dictionary: &'a Dictionary,
+    options: &'a PlayOptions,
+    rng: StdRng,
+    db: Stats,
+    game: MultiGame,
+}
+
+impl Session for MultiSession<'_> {
+    fn word_len(&self) -> usize {
+        self.game.word_len()
+    }
+
+    fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    fn frame(&self, typed: &str, shake: Option<usize>, notice: Option<&str>) -> String {
+        let PlayOptions {
+            tries,
+            show_stats,
+            theme,
+            ..
+        } = *self.options;
+        let (dictionary, game) = (self.dictionary, &self.game);
+        let title = format!("          wurdle x{}", game.boards().len());
+        let mut frame = boards_text(&title, game, typed, shake, notice, theme);
+        let won_attempt = match game.status() {
+            GameStatus::Won { attempts } => Some(attempts),
+            _ => None,
+        };
+        let game_over_text = match game.status() {
+            GameStatus::InProgress => None,
+            GameStatus::Won { .. } => Some("     you won!".to_string()),
+            GameStatus::Lost => Some(format!(
+                "you lost! the words were {}",
+                game.words().join(", ")
+            )),
+        };
+        if let Some(ref text) = game_over_text {
+            let summary = self.db.summary_for_boards(
+                dictionary.length,
+                &dictionary.name,
+                game.boards().len(),
+            );
+            let mut stats = stats_text(&summary, tries, won_attempt, theme) + "\n  restart? (y/n)";
+            if !show_stats {
+                stats = "press (enter) to continue".to_string();
             }
+            frame += &format!("\n{text}\n\n{stats}\n");
         }
+        frame
     }
 
-    pub fn won(&mut self, attempt: usize) {
-        self.won += 1;
-        self.played += 1;
-        self.streak += 1;
-        if self.streak > self.maxstreak {
-            self.maxstreak = self.streak;
+    fn guess(&mut self, input: &str) -> Result<Option<String>, GuessError> {
+        let game = &mut self.game;
+        game.guess(input)?;
+        if !game.is_over() {
+            return Ok(None);
         }
-        if attempt == 1 {
-            self.dist_1 += 1;
-        } else if attempt == 2 {
-            self.dist_2 += 1;
-        } else if attempt == 3 {
-            self.dist_3 += 1;
-        } else if attempt == 4 {
-            self.dist_4 += 1;
-        } else if attempt == 5 {
-            self.dist_5 += 1;
-        } else if attempt == 6 {
-            self.dist_6 += 1;
+        let won = matches!(game.status(), GameStatus::Won { .. });
+        self.db.record(
+            GameRecord::multi(&game.words(), game.guesses().to_vec(), won, Mode::Random)
+                .dictionary(&self.dictionary.name),
+        );
+        Ok(self
+            .db
+            .save()
+            .err()
+            .map(|e| format!("could not save stats: {e}")))
+    }
+
+    fn hint(&mut self) -> String {
+        "no hints with more than one board!".to_string()
+    }
+
+    fn restart(&mut self) {
+        let PlayOptions { tries, boards, .. } = *self.options;
+        let words = get_words(&mut self.rng, &self.dictionary.answers, boards);
+        self.game = MultiGame::new(&words, tries, self.dictionary.allowed.clone());
+    }
+}
+
+/// There's no answer until the guesses leave only one word. Nothing is recorded in the stats,
+/// the game doesn't play fair.
+fn play_absurd(dictionary: &Dictionary, options: &PlayOptions) -> (bool, Vec<String>, String) {
+    let Some(term) = new_terminal() else {
+        return (false, vec![], String::new());
+    };
+    let mut session = AbsurdSession {
+        dictionary,
+        options,
+        game: AbsurdGame::new(
+            &dictionary.answers,
+            options.tries,
+            dictionary.allowed.clone(),
+        ),
+    };
+    if !run_session(term, &mut session) {
+        return (false, vec![], String::new());
+    }
+
+    let game = &session.game;
+    let guesses = game.attempts().iter().map(|a| a.guess.clone()).collect();
+    match game.status() {
+        GameStatus::InProgress => (false, guesses, String::new()),
+        status => {
+            let won = matches!(status, GameStatus::Won { .. });
+            (won, guesses, game.candidates()[0].clone())
         }
     }
+}
 
-    pub fn lost(&mut self) {
-        self.streak = 0;
-        self.played += 1;
+struct AbsurdSession<'a> {
+    dictionary: &'a Dictionary,
+    options: &'a PlayOptions,
+    game: AbsurdGame,
+}
+
+impl Session for AbsurdSession<'_> {
+    fn word_len(&self) -> usize {
+        self.game.word_len()
     }
 
-    pub fn save(&self) {
-        let mut output = if Path::new(DB_FILE).exists() {
-            OpenOptions::new()
-                .read(true)
-                .write(true)
-                .open(Path::new(DB_FILE))
-                .unwrap()
-        } else {
-            OpenOptions::new()
-                .read(true)
-                .write(true)
-                .create_new(true)
-                .open(Path::new(DB_FILE))
-                .unwrap()
+    fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    fn frame(&self, typed: &str, shake: Option<usize>, notice: Option<&str>) -> String {
+        let game = &self.game;
+        let title = format!(
+            "      absurd wurdle\n{} word{} left",
+            game.remaining(),
+            if game.remaining() == 1 { "" } else { "s" }
+        );
+        let mut frame = board_text(&title, game, typed, shake, notice, self.options.theme);
+        let game_over_text = match game.status() {
+            GameStatus::InProgress => None,
+            GameStatus::Won { attempts } => Some(format!("you cornered it in {attempts}!")),
+            GameStatus::Lost => {
+                let left = game.candidates();
+                let mut text = format!(
+                    "you lost! it could still have been {}",
+                    left[..left.len().min(5)].join(", ")
+                );
+                if left.len() > 5 {
+                    text += &format!(" and {} more", left.len() - 5);
+                }
+                Some(text)
+            }
         };
-        write!(
-            output,
-            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
-            self.played,
-            self.won,
-            self.streak,
-            self.maxstreak,
-            self.dist_1,
-            self.dist_2,
-            self.dist_3,
-            self.dist_4,
-            self.dist_5,
-            self.dist_6
-        )
-        .unwrap();
+        if let Some(ref text) = game_over_text {
+            frame += &format!("\n{text}\n\n  restart? (y/n)\n");
+        }
+        frame
+    }
+
+    fn guess(&mut self, input: &str) -> Result<Option<String>, GuessError> {
+        self.game.guess(input).map(|_| None)
+    }
+
+    fn hint(&mut self) -> String {
+        "no hints in absurd mode!".to_string()
+    }
+
+    fn restart(&mut self) {
+        let dictionary = self.dictionary;
+        self.game = AbsurdGame::new(
+            &dictionary.answers,
+            self.options.tries,
+            dictionary.allowed.clone(),
+        );
     }
 }
@@ -0,0 +1,171 @@
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::{
+    game::{GameStatus, GuessError, KeyState, WurdleGame},
+    get_word,
+};
+
+pub const BOARD_COUNTS: [usize; 4] = [1, 2, 4, 8];
+
+/// Guesses you get by default for `boards` boards: 6 for one, 9 for four, 13 for eight.
+pub fn default_tries(boards: usize) -> usize {
+    if boards == 1 {
+        6
+    } else {
+        boards + 5
+    }
+}
+
+/// `count` different words from `words`, or as many as there are if that's fewer.
+pub fn get_words(rng: &mut impl Rng, words: &[String], count: usize) -> Vec<String> {
+    let mut picked: Vec<String> = vec![];
+    while picked.len() < count.min(words.len()) {
+        let word = get_word(rng, words);
+        if !picked.contains(&word) {
+            picked.push(word);
+        }
+    }
+    picked
+}
+
+/// Several games of wurdle played at once, every guess goes to every board that isn't solved yet.
+pub struct MultiGame {
+    boards: Vec<WurdleGame>,
+    tries: usize,
+    guesses: Vec<String>,
+}
+
+impl MultiGame {
+    pub fn new(words: &[String], tries: usize, allowed: HashSet<String>) -> Self {
+        Self {
+            boards: words
+                .iter()
+                .map(|word| WurdleGame::new(word, tries, allowed.clone()))
+                .collect(),
+            tries: tries.max(1),
+            guesses: vec![],
+        }
+    }
+
+    pub fn guess(&mut self, input: &str) -> Result<(), GuessError> {
+        let mut open = self.boards.iter_mut().filter(|b| !b.is_over());
+        // the first open board checks the guess, every board accepts the same words
+        let first = open.next().ok_or(GuessError::GameOver)?;
+        let guess = first.guess(input)?.guess;
+        for board in open {
+            board.guess(&guess)?;
+        }
+        self.guesses.push(guess);
+        Ok(())
+    }
+
+    pub fn boards(&self) -> &[WurdleGame] {
+        &self.boards
+    }
+
+    pub fn words(&self) -> Vec<String> {
+        self.boards.iter().map(|b| b.word().to_string()).collect()
+    }
+
+    pub fn guesses(&self) -> &[String] {
+        &self.guesses
+    }
+
+    pub fn tries(&self) -> usize {
+        self.tries
+    }
+
+    pub fn word_len(&self) -> usize {
+        self.boards[0].word_len()
+    }
+
+    pub fn solved(&self) -> usize {
+        self.boards
+            .iter()
+            .filter(|b| matches!(b.status(), GameStatus::Won { .. }))
+            .count()
+    }
+
+    pub fn status(&self) -> GameStatus {
+        if self.solved() == self.boards.len() {
+            GameStatus::Won {
+                attempts: self.guesses.len(),
+            }
+        } else if self.boards.iter().any(|b| b.status() == GameStatus::Lost) {
+            GameStatus::Lost
+        } else {
+            GameStatus::InProgress
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.status().is_over()
+    }
+
+    /// The best a key has done on any board that's still being played, so a letter only goes
+    /// grey once it's no use anywhere.
+    pub fn key_state(&self, c: char) -> KeyState {
+        let open: Vec<&WurdleGame> = self.boards.iter().filter(|b| !b.is_over()).collect();
+        let boards = if open.is_empty() {
+            self.boards.iter().collect()
+        } else {
+            open
+        };
+        boards
+            .iter()
+            .map(|b| b.key_state(c))
+            .max()
+            .unwrap_or(KeyState::Unused)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        game::{GameStatus, GuessError, KeyState},
+        multi::MultiGame,
+    };
+
+    fn game(words: &[&str], tries: usize) -> MultiGame {
+        let allowed = HashSet::from_iter(
+            ["CRANE", "HELLO", "WORLD", "ABOUT", "SHELL"]
+                .iter()
+                .map(|s| s.to_string()),
+        );
+        let words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+        MultiGame::new(&words, tries, allowed)
+    }
+
+    #[test]
+    fn solved_boards_stop_taking_guesses() {
+        let mut game = game(&["HELLO", "WORLD", "ABOUT", "CRANE"], 9);
+        assert!(matches!(game.guess("ZZZZZ"), Err(GuessError::NotAWord(_))));
+        assert!(game.guesses().is_empty());
+
+        game.guess("HELLO").unwrap();
+        game.guess("SHELL").unwrap();
+        assert_eq!(game.solved(), 1);
+        assert_eq!(game.boards()[0].attempts().len(), 1);
+        assert_eq!(game.boards()[1].attempts().len(), 2);
+        assert_eq!(game.key_state('E'), KeyState::Present);
+
+        for word in ["WORLD", "ABOUT", "CRANE"] {
+            game.guess(word).unwrap();
+        }
+        assert_eq!(game.status(), GameStatus::Won { attempts: 5 });
+        assert_eq!(game.guess("HELLO"), Err(GuessError::GameOver));
+    }
+
+    #[test]
+    fn lost_when_any_board_runs_out() {
+        let mut game = game(&["HELLO", "WORLD"], 2);
+        game.guess("HELLO").unwrap();
+        assert!(!game.is_over());
+        game.guess("CRANE").unwrap();
+        assert_eq!(game.status(), GameStatus::Lost);
+    }
+}
@@ -0,0 +1,483 @@
+use std::{
+    cmp::Reverse,
+    fmt, io,
+    time::{Duration, Instant},
+};
+
+use chrono::{NaiveDate, Utc};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board_text,
+    dictionary::Dictionary,
+    game::{GuessError, GuessFeedback, WurdleGame},
+    get_word,
+    multi::get_words,
+    play_loop,
+    stats::Stats,
+    theme::Theme,
+    tui::Terminal,
+    PlayOptions, Session,
+};
+
+pub const SPEEDRUN_WORDS: usize = 10;
+// how often the clock on screen is redrawn while nothing is being typed
+const REFRESH: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Race {
+    // as many words as possible before the clock runs out
+    TimeAttack { minutes: u64 },
+    // the same ten words for everyone using this seed, as fast as possible
+    Speedrun { seed: u64 },
+}
+
+impl fmt::Display for Race {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Race::TimeAttack { minutes } => write!(f, "time attack ({minutes} min)"),
+            Race::Speedrun { seed } => write!(f, "speedrun (seed {seed})"),
+        }
+    }
+}
+
+/// One word of a run, done either way.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Split {
+    pub word: String,
+    pub guesses: usize,
+    pub solved: bool,
+    // milliseconds from the start of the run
+    pub at: u64,
+}
+
+/// A finished run, kept in the stats file when it's a personal best.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RaceRecord {
+    pub race: Race,
+    pub date: NaiveDate,
+    pub length: usize,
+    pub dictionary: String,
+    pub tries: usize,
+    pub hard: bool,
+    pub splits: Vec<Split>,
+}
+
+impl RaceRecord {
+    pub fn solved(&self) -> usize {
+        self.splits.iter().filter(|s| s.solved).count()
+    }
+
+    /// Milliseconds until the last word was solved.
+    pub fn time(&self) -> u64 {
+        self.splits
+            .iter()
+            .rev()
+            .find(|s| s.solved)
+            .map_or(0, |s| s.at)
+    }
+
+    /// More words solved wins, then being quicker about it.
+    pub fn beats(&self, other: &RaceRecord) -> bool {
+        (self.solved(), Reverse(self.time())) > (other.solved(), Reverse(other.time()))
+    }
+
+    /// Whether the two runs were played under the same rules and can be compared.
+    pub fn same_race(&self, other: &RaceRecord) -> bool {
+        self.race == other.race
+            && self.length == other.length
+            && self.dictionary == other.dictionary
+            && self.tries == other.tries
+            && self.hard == other.hard
+    }
+}
+
+/// A race against the clock with no terminal attached. The caller says how much time has passed,
+/// so it can be played out in tests without waiting.
+pub struct Run<'a> {
+    race: Race,
+    dictionary: &'a Dictionary,
+    tries: usize,
+    hard: bool,
+    rng: StdRng,
+    // words still to come in a speedrun
+    queue: Vec<String>,
+    game: WurdleGame,
+    splits: Vec<Split>,
+    over: bool,
+}
+
+impl<'a> Run<'a> {
+    pub fn new(race: Race, dictionary: &'a Dictionary, tries: usize, hard: bool) -> Self {
+        let (mut rng, mut queue) = match race {
+            Race::TimeAttack { .. } => (StdRng::from_entropy(), vec![]),
+            Race::Speedrun { seed } => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut words = get_words(&mut rng, &dictionary.answers, SPEEDRUN_WORDS);
+                words.reverse();
+                (rng, words)
+            }
+        };
+        let word = queue
+            .pop()
+            .unwrap_or_else(|| get_word(&mut rng, &dictionary.answers));
+        Self {
+            race,
+            dictionary,
+            tries,
+            hard,
+            game: WurdleGame::new(&word, tries, dictionary.allowed.clone()).hard_mode(hard),
+            rng,
+            queue,
+            splits: vec![],
+            over: false,
+        }
+    }
+
+    /// Ends a time attack once its time is up.
+    pub fn tick(&mut self, elapsed: Duration) {
+        if self.time_left(elapsed) == Some(Duration::ZERO) {
+            self.over = true;
+        }
+    }
+
+    pub fn guess(&mut self, input: &str, elapsed: Duration) -> Result<GuessFeedback, GuessError> {
+        self.tick(elapsed);
+        if self.over {
+            return Err(GuessError::GameOver);
+        }
+        let feedback = self.game.guess(input)?;
+        if self.game.is_over() {
+            self.splits.push(Split {
+                word: self.game.word().to_string(),
+                guesses: self.game.attempts().len(),
+                solved: feedback.is_correct(),
+                at: elapsed.as_millis() as u64,
+            });
+            let next = match self.race {
+                Race::TimeAttack { .. } => Some(get_word(&mut self.rng, &self.dictionary.answers)),
+                Race::Speedrun { .. } => self.queue.pop(),
+            };
+            match next {
+                Some(word) => {
+                    self.game = WurdleGame::new(&word, self.tries, self.dictionary.allowed.clone())
+                        .hard_mode(self.hard)
+                }
+                None => self.over = true,
+            }
+        }
+        Ok(feedback)
+    }
+
+    /// What's left on the clock in a time attack, speedruns don't have a limit.
+    pub fn time_left(&self, elapsed: Duration) -> Option<Duration> {
+        match self.race {
+            Race::TimeAttack { minutes } => {
+                Some(Duration::from_secs(minutes * 60).saturating_sub(elapsed))
+            }
+            Race::Speedrun { .. } => None,
+        }
+    }
+
+    pub fn race(&self) -> Race {
+        self.race
+    }
+
+    pub fn game(&self) -> &WurdleGame {
+        &self.game
+    }
+
+    pub fn splits(&self) -> &[Split] {
+        &self.splits
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    pub fn record(&self) -> RaceRecord {
+        RaceRecord {
+            race: self.race,
+            date: Utc::now().date_naive(),
+            length: self.dictionary.length,
+            dictionary: self.dictionary.name.clone(),
+            tries: self.tries,
+            hard: self.hard,
+            splits: self.splits.clone(),
+        }
+    }
+}
+
+/// `ms` as minutes, seconds and tenths.
+pub fn clock(ms: u64) -> String {
+    format!("{}:{:02}.{}", ms / 60_000, ms / 1000 % 60, ms / 100 % 10)
+}
+
+/// One line per word with its time, how long it took on its own, and how far ahead (-) or
+/// behind (+) `best` was at the same point.
+pub fn splits_text(splits: &[Split], best: Option<&RaceRecord>) -> String {
+    let mut text = String::new();
+    let mut previous = 0;
+    for (i, split) in splits.iter().enumerate() {
+        // guesses it took, X for a miss
+        let guesses = if split.solved {
+            split.guesses.to_string()
+        } else {
+            "X".to_string()
+        };
+        text += &format!(
+            "{:>2}. {} {guesses:<2} {} (+{})",
+            i + 1,
+            split.word,
+            clock(split.at),
+            clock(split.at - previous)
+        );
+        if let Some(theirs) = best.and_then(|b| b.splits.get(i)) {
+            let (sign, diff) = if split.at > theirs.at {
+                ('+', split.at - theirs.at)
+            } else {
+                ('-', theirs.at - split.at)
+            };
+            text += &format!("  {sign}{}", clock(diff));
+        }
+        text += "\n";
+        previous = split.at;
+    }
+    text
+}
+
+/// A personal best in one line.
+pub fn best_text(best: &RaceRecord) -> String {
+    let words = match best.race {
+        Race::Speedrun { .. } => format!("{}/{SPEEDRUN_WORDS}", best.solved()),
+        Race::TimeAttack { .. } => best.solved().to_string(),
+    };
+    format!(
+        "{}{}: {words} solved in {} on {}",
+        best.race,
+        if best.hard { " [hard]" } else { "" },
+        clock(best.time()),
+        best.date
+    )
+}
+
+/// Plays `race` until the player quits, saving personal bests as they're set. Hard mode and the
+/// number of tries come from `options`.
+pub fn play_race(dictionary: &Dictionary, race: Race, options: &PlayOptions) -> io::Result<()> {
+    let mut term = Terminal::new()?;
+    let run = Run::new(race, dictionary, options.tries, options.hard);
+    let db = Stats::read();
+    let mut session = RaceSession {
+        dictionary,
+        options,
+        best: db.best(&run.record()).cloned(),
+        db,
+        run,
+        started: Instant::now(),
+        results: None,
+    };
+    play_loop(&mut term, &mut session)
+}
+
+struct RaceSession<'a> {
+    dictionary: &'a Dictionary,
+    options: &'a PlayOptions,
+    db: Stats,
+    run: Run<'a>,
+    best: Option<RaceRecord>,
+    started: Instant,
+    // the splits and how they compare, once the run is over
+    results: Option<String>,
+}
+
+impl RaceSession<'_> {
+    fn finish(&mut self) {
+        let record = self.run.record();
+        let mut text = format!("          {}\n==========================\n\n", record.race);
+        text += &splits_text(&record.splits, self.best.as_ref());
+        text += &format!("\n{}\n", best_text(&record));
+        if self.db.record_best(record) {
+            text += "new personal best!\n";
+            if let Err(e) = self.db.save() {
+                text += &format!("could not save it: {e}\n");
+            }
+        } else if let Some(best) = &self.best {
+            text += &format!("best {}\n", best_text(best));
+        }
+        text += "\n  again? (y/n)\n";
+        self.results = Some(text);
+    }
+}
+
+impl Session for RaceSession<'_> {
+    fn word_len(&self) -> usize {
+        self.run.game().word_len()
+    }
+
+    fn is_over(&self) -> bool {
+        self.run.is_over()
+    }
+
+    fn frame(&self, typed: &str, shake: Option<usize>, notice: Option<&str>) -> String {
+        match &self.results {
+            Some(results) => results.clone(),
+            None => run_text(
+                &self.run,
+                self.started.elapsed(),
+                typed,
+                shake,
+                notice,
+                self.best.as_ref(),
+                self.options.theme,
+            ),
+        }
+    }
+
+    fn guess(&mut self, input: &str) -> Result<Option<String>, GuessError> {
+        let done = self.run.splits().len();
+        let result = self.run.guess(input, self.started.elapsed());
+        if self.run.is_over() {
+            // out of words or out of time, either way it's the results next
+            self.finish();
+            return Ok(None);
+        }
+        result?;
+        Ok(self.run.splits().get(done).map(|split| {
+            if split.solved {
+                format!("{} in {}", split.word, clock(split.at))
+            } else {
+                format!("missed {}", split.word)
+            }
+        }))
+    }
+
+    fn hint(&mut self) -> String {
+        "no hints against the clock!".to_string()
+    }
+
+    fn restart(&mut self) {
+        let PlayOptions { tries, hard, .. } = *self.options;
+        self.run = Run::new(self.run.race(), self.dictionary, tries, hard);
+        self.best = self.db.best(&self.run.record()).cloned();
+        self.started = Instant::now();
+        self.results = None;
+    }
+
+    fn refresh(&self) -> Option<Duration> {
+        (!self.run.is_over()).then_some(REFRESH)
+    }
+
+    fn tick(&mut self) {
+        self.run.tick(self.started.elapsed());
+        if self.run.is_over() {
+            self.finish();
+        }
+    }
+}
+
+fn run_text(
+    run: &Run,
+    elapsed: Duration,
+    typed: &str,
+    shake: Option<usize>,
+    notice: Option<&str>,
+    best: Option<&RaceRecord>,
+    theme: Theme,
+) -> String {
+    let solved = run.splits().iter().filter(|s| s.solved).count();
+    let status = match (run.race(), run.time_left(elapsed)) {
+        (Race::TimeAttack { .. }, Some(left)) => {
+            format!("{} left, {solved} solved", clock(left.as_millis() as u64))
+        }
+        _ => format!(
+            "{}, word {}/{SPEEDRUN_WORDS}",
+            clock(elapsed.as_millis() as u64),
+            run.splits().len() + 1
+        ),
+    };
+    let title = format!("    {}\n{status}", run.race());
+    let mut text = board_text(&title, run.game(), typed, shake, notice, theme);
+    text += "\n";
+    text += &splits_text(run.splits(), best);
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        dictionary::Dictionary,
+        game::GuessError,
+        race::{clock, Race, Run, SPEEDRUN_WORDS},
+    };
+
+    fn dictionary() -> Dictionary {
+        let words = [
+            "HELLO", "WORLD", "CRANE", "ABOUT", "SHELL", "PUDGY", "SLOTH", "MIGHT", "FJORD",
+            "QUICK", "BLAST", "TRAIN",
+        ];
+        Dictionary::from_words(
+            "test",
+            words.iter().map(|w| w.to_string()).collect(),
+            vec![],
+            5,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn speedrun_is_the_same_every_time() {
+        let dictionary = dictionary();
+        let race = Race::Speedrun { seed: 7 };
+        let mut run = Run::new(race, &dictionary, 6, false);
+        let mut again = Run::new(race, &dictionary, 6, false);
+
+        for i in 0..SPEEDRUN_WORDS {
+            assert_eq!(run.game().word(), again.game().word());
+            let word = run.game().word().to_string();
+            let elapsed = Duration::from_secs(10 * (i as u64 + 1));
+            if i == 0 {
+                // the first word is missed, the run carries on
+                for _ in 0..6 {
+                    let guess = if word == "HELLO" { "WORLD" } else { "HELLO" };
+                    run.guess(guess, elapsed).unwrap();
+                }
+            } else {
+                run.guess(&word, elapsed).unwrap();
+            }
+            again.guess(&word, elapsed / 2).unwrap();
+        }
+        assert!(run.is_over());
+        let (record, best) = (run.record(), again.record());
+        assert_eq!(record.solved(), SPEEDRUN_WORDS - 1);
+        assert!(!record.splits[0].solved);
+        assert_eq!(record.time(), 100_000);
+        assert!(best.beats(&record));
+        assert!(best.same_race(&record));
+    }
+
+    #[test]
+    fn time_attack_stops_the_clock() {
+        let dictionary = dictionary();
+        let mut run = Run::new(Race::TimeAttack { minutes: 1 }, &dictionary, 6, false);
+        let word = run.game().word().to_string();
+        run.guess(&word, Duration::from_secs(20)).unwrap();
+        assert_eq!(run.splits().len(), 1);
+        assert_eq!(
+            run.time_left(Duration::from_secs(50)),
+            Some(Duration::from_secs(10))
+        );
+
+        let word = run.game().word().to_string();
+        assert_eq!(
+            run.guess(&word, Duration::from_secs(61)),
+            Err(GuessError::GameOver)
+        );
+        assert!(run.is_over());
+        assert_eq!(run.record().solved(), 1);
+        assert_eq!(clock(83_456), "1:23.4");
+    }
+}
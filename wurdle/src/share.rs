@@ -0,0 +1,74 @@
+use std::{fs, io, path::Path};
+
+use crate::game::{evaluate_slice, GuessFeedback, LetterResult};
+
+pub fn square(result: LetterResult) -> char {
+    match result {
+        LetterResult::Correct => '🟩',
+        LetterResult::Present => '🟨',
+        LetterResult::Absent => '⬛',
+    }
+}
+
+/// A spoiler-free summary of a finished game: the header line and one row of squares per guess.
+pub fn share_text(
+    puzzle: Option<usize>,
+    attempts: &[GuessFeedback],
+    won: bool,
+    tries: usize,
+    hard: bool,
+) -> String {
+    let mut text = String::from("wurdle");
+    if let Some(puzzle) = puzzle {
+        text += &format!(" #{puzzle}");
+    }
+    if won {
+        text += &format!(" {}/{tries}", attempts.len());
+    } else {
+        text += &format!(" X/{tries}");
+    }
+    if hard {
+        text.push('*');
+    }
+    text.push('\n');
+
+    for attempt in attempts {
+        text.push('\n');
+        text.extend(attempt.letters.iter().map(|l| square(*l)));
+    }
+    text
+}
+
+// for games that only survive as a stats record
+pub fn feedback_for(word: &str, guesses: &[String]) -> Vec<GuessFeedback> {
+    let word: Vec<char> = word.chars().collect();
+    guesses
+        .iter()
+        .map(|guess| GuessFeedback {
+            guess: guess.clone(),
+            letters: evaluate_slice(&guess.chars().collect::<Vec<char>>(), &word),
+        })
+        .collect()
+}
+
+pub fn write_share(path: &Path, text: &str) -> io::Result<()> {
+    fs::write(path, format!("{text}\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::share::{feedback_for, share_text};
+
+    #[test]
+    fn grid() {
+        let attempts = feedback_for("HELLO", &["WORLD".into(), "HELLO".into()]);
+        assert_eq!(
+            share_text(Some(12), &attempts, true, 6, false),
+            "wurdle #12 2/6\n\n⬛🟨⬛🟩⬛\n🟩🟩🟩🟩🟩"
+        );
+        assert_eq!(
+            share_text(None, &attempts[..1], false, 1, true),
+            "wurdle X/1*\n\n⬛🟨⬛🟩⬛"
+        );
+    }
+}
@@ -0,0 +1,371 @@
+use std::{cmp::Ordering, collections::HashSet, fmt, str::FromStr, sync::OnceLock};
+
+use rayon::prelude::*;
+
+use crate::{
+    dictionary::Dictionary,
+    game::{evaluate_into, GuessFeedback, LetterResult, WurdleGame},
+};
+
+/// Feedback packed into a base 3 number, one digit per letter.
+pub type Pattern = u16;
+
+pub fn pattern(guess: &[char], answer: &[char]) -> Pattern {
+    let mut result = [LetterResult::Absent; 8];
+    let result = &mut result[..guess.len()];
+    evaluate_into(guess, answer, result);
+    encode(result)
+}
+
+pub fn encode(letters: &[LetterResult]) -> Pattern {
+    letters.iter().rev().fold(0, |acc, l| {
+        acc * 3
+            + match l {
+                LetterResult::Absent => 0,
+                LetterResult::Present => 1,
+                LetterResult::Correct => 2,
+            }
+    })
+}
+
+fn chars(word: &str) -> Vec<char> {
+    word.chars().collect()
+}
+
+/// Keeps the words that would have produced every piece of feedback in `history`.
+pub fn filter_candidates(candidates: &[String], history: &[GuessFeedback]) -> Vec<String> {
+    let history: Vec<(Vec<char>, Pattern)> = history
+        .iter()
+        .map(|f| (chars(&f.guess), encode(&f.letters)))
+        .collect();
+    candidates
+        .iter()
+        .filter(|word| {
+            let word = chars(word);
+            history
+                .iter()
+                .all(|(guess, p)| guess.len() == word.len() && pattern(guess, &word) == *p)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Expected information in bits from playing `guess` when the answer is one of `candidates`.
+pub fn entropy(guess: &[char], candidates: &[Vec<char>]) -> f64 {
+    let mut counts = vec![0u32; 3usize.pow(guess.len() as u32)];
+    for answer in candidates {
+        counts[pattern(guess, answer) as usize] += 1;
+    }
+    let total = candidates.len() as f64;
+    counts
+        .iter()
+        .filter(|c| **c > 0)
+        .map(|c| {
+            let p = *c as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub guess: String,
+    pub bits: f64,
+    // could this guess be the answer itself?
+    pub candidate: bool,
+}
+
+/// Ranks every word in `guesses` by entropy over `candidates`, best first. Ties go to words that
+/// could still be the answer.
+pub fn rank_guesses(guesses: &[String], candidates: &[String]) -> Vec<Hint> {
+    let candidate_chars: Vec<Vec<char>> = candidates.iter().map(|w| chars(w)).collect();
+    let candidate_set: HashSet<&String> = HashSet::from_iter(candidates);
+    let mut hints: Vec<Hint> = guesses
+        .iter()
+        .map(|guess| Hint {
+            guess: guess.clone(),
+            bits: entropy(&chars(guess), &candidate_chars),
+            candidate: candidate_set.contains(guess),
+        })
+        .collect();
+    hints.sort_by(|a, b| {
+        b.bits
+            .partial_cmp(&a.bits)
+            .unwrap_or(Ordering::Equal)
+            .then(b.candidate.cmp(&a.candidate))
+            .then(a.guess.cmp(&b.guess))
+    });
+    hints
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    // highest entropy over every allowed word
+    Entropy,
+    // highest entropy, but only ever guessing words that could be the answer
+    Candidates,
+    // the first word that could be the answer, as a baseline
+    First,
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "entropy" => Ok(Strategy::Entropy),
+            "candidates" => Ok(Strategy::Candidates),
+            "first" => Ok(Strategy::First),
+            _ => Err(format!(
+                "unknown strategy '{s}', expected entropy, candidates or first"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strategy::Entropy => write!(f, "entropy"),
+            Strategy::Candidates => write!(f, "candidates"),
+            Strategy::First => write!(f, "first"),
+        }
+    }
+}
+
+pub struct Solver {
+    answers: Vec<String>,
+    guesses: Vec<String>,
+    strategy: Strategy,
+    opener: Option<String>,
+    // the first guess never depends on anything, so it only gets worked out once for each mode
+    openers: [OnceLock<String>; 2],
+}
+
+impl Solver {
+    pub fn new(dictionary: &Dictionary) -> Self {
+        let mut guesses: Vec<String> = dictionary.allowed.iter().cloned().collect();
+        guesses.sort();
+        Self {
+            answers: dictionary.answers.clone(),
+            guesses,
+            strategy: Strategy::Entropy,
+            opener: None,
+            openers: [OnceLock::new(), OnceLock::new()],
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    pub fn answers(&self) -> &[String] {
+        &self.answers
+    }
+
+    /// Always open with `opener` instead of the highest entropy word.
+    pub fn with_opener(mut self, opener: &str) -> Self {
+        self.opener = Some(opener.to_uppercase());
+        self
+    }
+
+    pub fn candidates(&self, history: &[GuessFeedback]) -> Vec<String> {
+        filter_candidates(&self.answers, history)
+    }
+
+    /// The top `n` next guesses. In hard mode only words that could still be the answer are
+    /// considered, since those always satisfy the hard mode rules.
+    pub fn hints(&self, history: &[GuessFeedback], hard: bool, n: usize) -> Vec<Hint> {
+        let candidates = self.candidates(history);
+        if candidates.len() <= 2 || self.strategy == Strategy::First {
+            return candidates
+                .into_iter()
+                .take(n)
+                .map(|guess| Hint {
+                    guess,
+                    bits: 1.0,
+                    candidate: true,
+                })
+                .collect();
+        }
+        let pool = if hard || self.strategy == Strategy::Candidates {
+            &candidates
+        } else {
+            &self.guesses
+        };
+        let mut hints = rank_guesses(pool, &candidates);
+        hints.truncate(n);
+        hints
+    }
+
+    pub fn best_guess(&self, history: &[GuessFeedback], hard: bool) -> Option<String> {
+        if history.is_empty() {
+            return Some(self.opener(hard).to_string());
+        }
+        self.hints(history, hard, 1).pop().map(|hint| hint.guess)
+    }
+
+    pub fn opener(&self, hard: bool) -> &str {
+        if let Some(opener) = &self.opener {
+            return opener;
+        }
+        self.openers[hard as usize].get_or_init(|| {
+            self.hints(&[], hard, 1)
+                .pop()
+                .map(|hint| hint.guess)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Plays `answer` to the end and returns every guess made. The game is lost if the last one
+    /// isn't correct.
+    pub fn solve(&self, answer: &str, tries: usize, hard: bool) -> Vec<GuessFeedback> {
+        let allowed: HashSet<String> = HashSet::from_iter(self.guesses.iter().cloned());
+        let mut game = WurdleGame::new(answer, tries, allowed).hard_mode(hard);
+        while !game.is_over() {
+            let Some(guess) = self.best_guess(game.attempts(), hard) else {
+                break;
+            };
+            if game.guess(&guess).is_err() {
+                break;
+            }
+        }
+        game.attempts().to_vec()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BenchReport {
+    pub games: usize,
+    // distribution[i] = answers solved in i + 1 guesses
+    pub distribution: Vec<usize>,
+    pub failures: Vec<String>,
+    // the answers that took the most guesses, most first
+    pub worst: Vec<(String, usize)>,
+}
+
+impl BenchReport {
+    pub fn solved(&self) -> usize {
+        self.distribution.iter().sum()
+    }
+
+    pub fn mean(&self) -> f64 {
+        let total: usize = self
+            .distribution
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (i + 1) * n)
+            .sum();
+        total as f64 / self.solved().max(1) as f64
+    }
+}
+
+/// Runs the solver against every one of its answers in parallel.
+pub fn benchmark(solver: &Solver, tries: usize, hard: bool, worst: usize) -> BenchReport {
+    // work the opener out once up front instead of in every thread at the same time
+    solver.opener(hard);
+
+    let results: Vec<(String, Option<usize>)> = solver
+        .answers
+        .par_iter()
+        .map(|answer| {
+            let attempts = solver.solve(answer, tries, hard);
+            let solved = attempts.last().is_some_and(|a| a.is_correct());
+            (answer.clone(), solved.then_some(attempts.len()))
+        })
+        .collect();
+
+    let mut report = BenchReport {
+        games: results.len(),
+        distribution: vec![0; tries.max(1)],
+        ..Default::default()
+    };
+    let mut solved: Vec<(String, usize)> = vec![];
+    for (answer, guesses) in results {
+        match guesses {
+            Some(guesses) => {
+                if let Some(count) = report.distribution.get_mut(guesses - 1) {
+                    *count += 1;
+                }
+                solved.push((answer, guesses));
+            }
+            None => report.failures.push(answer),
+        }
+    }
+    solved.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    solved.truncate(worst);
+    report.worst = solved;
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dictionary::Dictionary,
+        share::feedback_for,
+        solver::{benchmark, filter_candidates, Solver, Strategy},
+    };
+
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn filter() {
+        let candidates = words(&["HELLO", "HELPS", "WORLD", "CELLO"]);
+        let history = feedback_for("HELLO", &["HELPS".into()]);
+        assert_eq!(filter_candidates(&candidates, &history), words(&["HELLO"]));
+    }
+
+    #[test]
+    fn opener_for_each_mode() {
+        let dictionary = Dictionary::from_words(
+            "test",
+            words(&["CELLO", "HELLO", "JELLO", "MELLO"]),
+            words(&["CHJMA"]),
+            5,
+        )
+        .unwrap();
+        let solver = Solver::new(&dictionary);
+        assert_eq!(solver.opener(false), "CHJMA");
+        assert_ne!(solver.opener(true), "CHJMA");
+        assert_eq!(solver.opener(false), "CHJMA");
+
+        let solver = solver.with_opener("hello");
+        assert_eq!(solver.opener(false), "HELLO");
+        assert_eq!(solver.opener(true), "HELLO");
+    }
+
+    #[test]
+    fn solves_everything_it_is_given() {
+        let dictionary = Dictionary::from_words(
+            "test",
+            words(&[
+                "CRANE", "SLATE", "HELLO", "CELLO", "JELLO", "WORLD", "WORDS",
+            ]),
+            vec![],
+            5,
+        )
+        .unwrap();
+        let solver = Solver::new(&dictionary);
+        for answer in &dictionary.answers {
+            let attempts = solver.solve(answer, 6, false);
+            assert!(attempts.last().unwrap().is_correct(), "{answer}");
+        }
+
+        for strategy in [Strategy::Entropy, Strategy::Candidates, Strategy::First] {
+            let solver = Solver::new(&dictionary).with_strategy(strategy);
+            let report = benchmark(&solver, 6, false, 3);
+            assert_eq!(report.games, 7);
+            assert_eq!(report.solved(), 7, "{strategy}");
+            assert!(report.failures.is_empty());
+            assert_eq!(report.worst.len(), 3);
+            assert!(report.mean() >= 1.0);
+        }
+    }
+}
@@ -0,0 +1,548 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{dictionary::DEFAULT_DICTIONARY, race::RaceRecord};
+
+pub const STATS_VERSION: u32 = 2;
+
+// the ten-line file older versions wrote into the working directory
+const LEGACY_DB_FILE: &str = "./.wurdle";
+const DB_FILE: &str = "stats.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Random,
+    Daily,
+    // a past daily played again with --date
+    Replay,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GameRecord {
+    pub date: NaiveDate,
+    // every answer, space separated, when more than one board was played
+    pub word: String,
+    pub guesses: Vec<String>,
+    pub won: bool,
+    pub mode: Mode,
+    #[serde(default)]
+    pub hard: bool,
+    #[serde(default = "default_length")]
+    pub length: usize,
+    #[serde(default = "default_dictionary")]
+    pub dictionary: String,
+    #[serde(default)]
+    pub puzzle: Option<usize>,
+    #[serde(default = "default_boards")]
+    pub boards: usize,
+}
+
+fn default_length() -> usize {
+    5
+}
+
+fn default_boards() -> usize {
+    1
+}
+
+fn default_dictionary() -> String {
+    DEFAULT_DICTIONARY.to_string()
+}
+
+impl GameRecord {
+    pub fn new(word: &str, guesses: Vec<String>, won: bool, mode: Mode) -> Self {
+        Self {
+            date: Utc::now().date_naive(),
+            word: word.to_string(),
+            guesses,
+            won,
+            mode,
+            hard: false,
+            length: word.chars().count(),
+            dictionary: DEFAULT_DICTIONARY.to_string(),
+            puzzle: None,
+            boards: 1,
+        }
+    }
+
+    /// A game played on several boards at once, won only if every board was solved.
+    pub fn multi(words: &[String], guesses: Vec<String>, won: bool, mode: Mode) -> Self {
+        Self {
+            length: words.first().map_or(0, |w| w.chars().count()),
+            boards: words.len(),
+            ..Self::new(&words.join(" "), guesses, won, mode)
+        }
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.word.split(' ')
+    }
+
+    pub fn puzzle(mut self, puzzle: Option<usize>) -> Self {
+        self.puzzle = puzzle;
+        self
+    }
+
+    pub fn dictionary(mut self, dictionary: &str) -> Self {
+        self.dictionary = dictionary.to_string();
+        self
+    }
+
+    pub fn hard(mut self, hard: bool) -> Self {
+        self.hard = hard;
+        self
+    }
+
+    fn is_daily(&self, puzzle: usize, length: usize, dictionary: &str) -> bool {
+        self.mode == Mode::Daily
+            && self.puzzle == Some(puzzle)
+            && self.length == length
+            && self.dictionary == dictionary
+            && self.boards == 1
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub played: usize,
+    pub won: usize,
+    pub streak: usize,
+    pub maxstreak: usize,
+    // distribution[i] = games won in i + 1 guesses
+    pub distribution: Vec<usize>,
+}
+
+impl Summary {
+    pub fn add(&mut self, record: &GameRecord) {
+        self.played += 1;
+        if record.won {
+            self.won += 1;
+            self.streak += 1;
+            self.maxstreak = self.maxstreak.max(self.streak);
+            let attempt = record.guesses.len().max(1);
+            if self.distribution.len() < attempt {
+                self.distribution.resize(attempt, 0);
+            }
+            self.distribution[attempt - 1] += 1;
+        } else {
+            self.streak = 0;
+        }
+    }
+
+    pub fn dist(&self, attempt: usize) -> usize {
+        self.distribution.get(attempt - 1).copied().unwrap_or(0)
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        if self.played == 0 {
+            0.0
+        } else {
+            f64::trunc((self.won as f64) / (self.played as f64) * 100.0 * 100.0) / 100.0
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StatsFile {
+    version: u32,
+    // totals carried over from the legacy format, which kept no history
+    baseline: Summary,
+    history: Vec<GameRecord>,
+    #[serde(default)]
+    bests: Vec<RaceRecord>,
+}
+
+pub struct Stats {
+    path: PathBuf,
+    pub baseline: Summary,
+    pub history: Vec<GameRecord>,
+    // the best time attack and speedrun for each set of rules
+    pub bests: Vec<RaceRecord>,
+}
+
+pub fn default_path() -> PathBuf {
+    match dirs::data_dir() {
+        Some(dir) => dir.join("wurdle").join(DB_FILE),
+        None => PathBuf::from("./.wurdle.json"),
+    }
+}
+
+impl Stats {
+    pub fn read() -> Self {
+        let path = default_path();
+        if !path.exists() {
+            if let Some(baseline) = read_legacy(Path::new(LEGACY_DB_FILE)) {
+                let stats = Self {
+                    path,
+                    baseline,
+                    history: vec![],
+                    bests: vec![],
+                };
+                if let Err(e) = stats.save() {
+                    eprintln!("could not migrate {LEGACY_DB_FILE}: {e}");
+                }
+                return stats;
+            }
+        }
+        Self::read_from(path)
+    }
+
+    /// Reads the stats file at `path`. A missing file gives empty stats; an unreadable one is
+    /// moved aside to `<path>.bak` so the next save doesn't destroy it.
+    pub fn read_from(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let empty = |path| Self {
+            path,
+            baseline: Summary::default(),
+            history: vec![],
+            bests: vec![],
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return empty(path),
+            Err(e) => {
+                eprintln!("could not read {}: {e}", path.display());
+                return empty(path);
+            }
+        };
+        match serde_json::from_str::<StatsFile>(&contents) {
+            Ok(file) if file.version <= STATS_VERSION => Self {
+                path,
+                baseline: file.baseline,
+                history: file.history,
+                bests: file.bests,
+            },
+            Ok(file) => {
+                eprintln!(
+                    "{} is from a newer version ({}), starting over",
+                    path.display(),
+                    file.version
+                );
+                backup(&path);
+                empty(path)
+            }
+            Err(e) => {
+                eprintln!("{} is corrupt ({e}), starting over", path.display());
+                backup(&path);
+                empty(path)
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Forgets everything, keeping a copy of the old file at `<path>.bak`.
+    pub fn reset(&mut self) -> io::Result<()> {
+        if self.path.exists() {
+            let mut bak = self.path.clone().into_os_string();
+            bak.push(".bak");
+            fs::copy(&self.path, bak)?;
+        }
+        self.baseline = Summary::default();
+        self.history.clear();
+        self.bests.clear();
+        self.save()
+    }
+
+    pub fn record(&mut self, record: GameRecord) {
+        self.history.push(record);
+    }
+
+    /// Records today's daily, replacing what was saved of it before. Dailies are recorded after
+    /// every guess, so quitting part way through still counts as playing it.
+    pub fn record_daily(&mut self, record: GameRecord) {
+        let puzzle = record.puzzle.unwrap_or_default();
+        let saved = self
+            .history
+            .iter_mut()
+            .find(|r| r.is_daily(puzzle, record.length, &record.dictionary));
+        match saved {
+            Some(saved) => *saved = record,
+            None => self.history.push(record),
+        }
+    }
+
+    pub fn summary(&self) -> Summary {
+        let mut summary = self.baseline.clone();
+        for record in &self.history {
+            summary.add(record);
+        }
+        summary
+    }
+
+    /// Stats for one word length and dictionary, so six letter games don't drag down the five
+    /// letter win rate. The legacy baseline only ever covered the built-in five letter words.
+    pub fn summary_for(&self, length: usize, dictionary: &str) -> Summary {
+        self.summary_for_boards(length, dictionary, 1)
+    }
+
+    /// Like [`Stats::summary_for`], but for games played on `boards` boards at once.
+    pub fn summary_for_boards(&self, length: usize, dictionary: &str, boards: usize) -> Summary {
+        let mut summary = if length == 5 && dictionary == DEFAULT_DICTIONARY && boards == 1 {
+            self.baseline.clone()
+        } else {
+            Summary::default()
+        };
+        for record in self.records_for(length, dictionary, boards) {
+            summary.add(record);
+        }
+        summary
+    }
+
+    pub fn records_for<'a>(
+        &'a self,
+        length: usize,
+        dictionary: &'a str,
+        boards: usize,
+    ) -> impl Iterator<Item = &'a GameRecord> {
+        self.history
+            .iter()
+            .filter(move |r| r.length == length && r.dictionary == dictionary && r.boards == boards)
+    }
+
+    /// The personal best for the race `record` was run under.
+    pub fn best(&self, record: &RaceRecord) -> Option<&RaceRecord> {
+        self.bests.iter().find(|b| b.same_race(record))
+    }
+
+    /// Keeps `record` if it beats the personal best for its race, returning whether it did. Runs
+    /// that solved nothing never count.
+    pub fn record_best(&mut self, record: RaceRecord) -> bool {
+        if record.solved() == 0 {
+            return false;
+        }
+        match self.bests.iter_mut().find(|b| b.same_race(&record)) {
+            Some(best) if !record.beats(best) => false,
+            Some(best) => {
+                *best = record;
+                true
+            }
+            None => {
+                self.bests.push(record);
+                true
+            }
+        }
+    }
+
+    pub fn daily_played(
+        &self,
+        puzzle: usize,
+        length: usize,
+        dictionary: &str,
+    ) -> Option<&GameRecord> {
+        self.history
+            .iter()
+            .find(|r| r.is_daily(puzzle, length, dictionary))
+    }
+
+    /// Writes to a temporary file next to the real one and renames it over, so a crash mid-write
+    /// never leaves a half-written stats file behind.
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = StatsFile {
+            version: STATS_VERSION,
+            baseline: self.baseline.clone(),
+            history: self.history.clone(),
+            bests: self.bests.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(io::Error::other)?;
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+fn backup(path: &Path) {
+    let mut bak = path.to_path_buf().into_os_string();
+    bak.push(".bak");
+    if let Err(e) = fs::rename(path, &bak) {
+        eprintln!("could not back up {}: {e}", path.display());
+    }
+}
+
+// played, won, streak, maxstreak, then dist_1..dist_6, one number per line
+fn read_legacy(path: &Path) -> Option<Summary> {
+    let lines = fs::read_to_string(path).ok()?;
+    // only the first ten lines count, the old save() never truncated so junk can trail them
+    let numbers = lines
+        .lines()
+        .take(10)
+        .map(|line| line.trim().parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>()
+        .ok()?;
+    if numbers.len() < 10 {
+        return None;
+    }
+
+    Some(Summary {
+        played: numbers[0],
+        won: numbers[1],
+        streak: numbers[2],
+        maxstreak: numbers[3],
+        distribution: numbers[4..10].to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, path::PathBuf};
+
+    use crate::{
+        race::{Race, RaceRecord, Split},
+        stats::{read_legacy, GameRecord, Mode, Stats},
+    };
+
+    // a directory for each test, removed again once the test is done with it
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(test: &str) -> Self {
+            let dir = env::temp_dir().join(format!("wurdle-test-{}-{test}", std::process::id()));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.0.join(name)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = TempDir::new("round_trip");
+        let path = dir.path("stats.json");
+        let mut stats = Stats::read_from(&path);
+        stats.record(GameRecord::new(
+            "HELLO",
+            vec!["CRANE".into(), "HELLO".into()],
+            true,
+            Mode::Random,
+        ));
+        stats.record(GameRecord::new("WORLD", vec![], false, Mode::Daily));
+        stats.save().unwrap();
+
+        let summary = Stats::read_from(&path).summary();
+        assert_eq!(summary.played, 2);
+        assert_eq!(summary.won, 1);
+        assert_eq!(summary.streak, 0);
+        assert_eq!(summary.maxstreak, 1);
+        assert_eq!(summary.dist(2), 1);
+    }
+
+    #[test]
+    fn separate_lengths_and_dictionaries() {
+        let dir = TempDir::new("separate");
+        let mut stats = Stats::read_from(dir.path("stats.json"));
+        stats.record(GameRecord::new(
+            "HELLO",
+            vec!["HELLO".into()],
+            true,
+            Mode::Random,
+        ));
+        stats.record(GameRecord::new("PLANET", vec![], false, Mode::Random));
+        stats.record(
+            GameRecord::new("HELLO", vec!["HELLO".into()], true, Mode::Random).dictionary("mine"),
+        );
+
+        assert_eq!(stats.summary_for(5, "default").played, 1);
+        assert_eq!(stats.summary_for(6, "default").won, 0);
+        assert_eq!(stats.summary_for(5, "mine").streak, 1);
+        assert_eq!(stats.summary().played, 3);
+
+        let words = vec!["HELLO".to_string(), "WORLD".to_string()];
+        stats.record(GameRecord::multi(&words, vec![], false, Mode::Random));
+        assert_eq!(stats.summary_for(5, "default").played, 1);
+        assert_eq!(stats.summary_for_boards(5, "default", 2).played, 1);
+        assert_eq!(stats.history[3].words().count(), 2);
+    }
+
+    #[test]
+    fn daily_is_recorded_once() {
+        let dir = TempDir::new("daily");
+        let mut stats = Stats::read_from(dir.path("stats.json"));
+        let daily = |guesses: &[&str], won| {
+            let guesses = guesses.iter().map(|g| g.to_string()).collect();
+            GameRecord::new("HELLO", guesses, won, Mode::Daily).puzzle(Some(7))
+        };
+        stats.record_daily(daily(&["WORLD"], false));
+        assert!(stats.daily_played(7, 5, "default").is_some());
+
+        stats.record_daily(daily(&["WORLD", "HELLO"], true));
+        assert_eq!(stats.history.len(), 1);
+        assert!(stats.daily_played(7, 5, "default").unwrap().won);
+        assert!(stats.daily_played(8, 5, "default").is_none());
+    }
+
+    #[test]
+    fn personal_bests() {
+        let dir = TempDir::new("bests");
+        let path = dir.path("stats.json");
+        let mut stats = Stats::read_from(&path);
+        let run = |at: u64| RaceRecord {
+            race: Race::Speedrun { seed: 1 },
+            date: "2024-05-01".parse().unwrap(),
+            length: 5,
+            dictionary: "default".to_string(),
+            tries: 6,
+            hard: false,
+            splits: vec![Split {
+                word: "HELLO".to_string(),
+                guesses: 2,
+                solved: true,
+                at,
+            }],
+        };
+        assert!(stats.record_best(run(5000)));
+        assert!(!stats.record_best(run(6000)));
+        assert!(stats.record_best(run(4000)));
+        stats.save().unwrap();
+
+        let stats = Stats::read_from(&path);
+        assert_eq!(stats.bests.len(), 1);
+        assert_eq!(stats.best(&run(0)).map(|b| b.time()), Some(4000));
+    }
+
+    #[test]
+    fn corrupt_file_is_recovered() {
+        let dir = TempDir::new("corrupt");
+        let path = dir.path("stats.json");
+        fs::write(&path, "{\"version\": 2, \"hist").unwrap();
+        let stats = Stats::read_from(&path);
+        assert!(stats.history.is_empty());
+        assert!(!path.exists());
+        assert!(dir.path("stats.json.bak").exists());
+    }
+
+    #[test]
+    fn legacy() {
+        let dir = TempDir::new("legacy");
+        let path = dir.path("legacy");
+        fs::write(&path, "10\n7\n2\n4\n0\n1\n3\n2\n1\n0").unwrap();
+        let summary = read_legacy(&path).unwrap();
+        assert_eq!(summary.played, 10);
+        assert_eq!(summary.dist(3), 3);
+
+        // truncated by a partial write
+        fs::write(&path, "10\n7\n2").unwrap();
+        assert!(read_legacy(&path).is_none());
+    }
+}
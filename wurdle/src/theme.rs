@@ -0,0 +1,184 @@
+use std::{env, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{KeyState, LetterResult};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    // green and yellow backgrounds
+    #[default]
+    Classic,
+    // orange and blue, which stay apart for most kinds of colour blindness
+    Contrast,
+    // no colour at all, [A] is right, (A) is in the wrong spot
+    Mono,
+}
+
+/// What a letter on screen is telling the player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Correct,
+    Present,
+    Absent,
+    // a key that hasn't been guessed yet
+    Unused,
+    // a letter in a guess that was just rejected
+    Invalid,
+    // typed but not guessed yet, or an empty slot
+    Plain,
+}
+
+impl From<LetterResult> for Tile {
+    fn from(result: LetterResult) -> Self {
+        match result {
+            LetterResult::Correct => Tile::Correct,
+            LetterResult::Present => Tile::Present,
+            LetterResult::Absent => Tile::Absent,
+        }
+    }
+}
+
+impl From<KeyState> for Tile {
+    fn from(state: KeyState) -> Self {
+        match state {
+            KeyState::Correct => Tile::Correct,
+            KeyState::Present => Tile::Present,
+            KeyState::Absent => Tile::Absent,
+            KeyState::Unused => Tile::Unused,
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(Theme::Classic),
+            "contrast" => Ok(Theme::Contrast),
+            "mono" => Ok(Theme::Mono),
+            _ => Err(format!(
+                "unknown theme '{s}', expected classic, contrast or mono"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::Classic => write!(f, "classic"),
+            Theme::Contrast => write!(f, "contrast"),
+            Theme::Mono => write!(f, "mono"),
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Classic, Theme::Contrast, Theme::Mono];
+
+    /// The theme to use when none was asked for: mono if `NO_COLOR` is set, otherwise classic.
+    pub fn from_env() -> Self {
+        match env::var("NO_COLOR") {
+            Ok(value) if !value.is_empty() => Theme::Mono,
+            _ => Theme::Classic,
+        }
+    }
+
+    fn color(&self, tile: Tile) -> &'static str {
+        match (self, tile) {
+            (Theme::Mono, _) | (_, Tile::Absent | Tile::Plain) => "",
+            (Theme::Classic, Tile::Correct) => "\x1b[42m",
+            (Theme::Classic, Tile::Present) => "\x1b[43m",
+            (Theme::Classic, Tile::Invalid) => "\x1b[41m",
+            (Theme::Contrast, Tile::Correct) => "\x1b[48;5;208m",
+            (Theme::Contrast, Tile::Present) => "\x1b[44m",
+            (Theme::Contrast, Tile::Invalid) => "\x1b[7m",
+            (_, Tile::Unused) => "\x1b[47m",
+        }
+    }
+
+    /// One letter of a guess row or the keyboard. Mono tiles are three characters wide so the
+    /// brackets have somewhere to go, every other theme's are one.
+    pub fn tile(&self, c: char, tile: Tile) -> String {
+        if *self == Theme::Mono {
+            return match tile {
+                Tile::Correct => format!("[{c}]"),
+                Tile::Present => format!("({c})"),
+                Tile::Invalid => format!("!{c}!"),
+                _ => format!(" {c} "),
+            };
+        }
+        self.paint(&c.to_string(), tile)
+    }
+
+    /// A key on the on-screen keyboard.
+    pub fn key(&self, c: char, state: KeyState) -> String {
+        match (self, state) {
+            // without colour, keys that are already ruled out drop off the keyboard instead
+            (Theme::Mono, KeyState::Absent) => " · ".to_string(),
+            _ => self.tile(c, state.into()),
+        }
+    }
+
+    pub fn paint(&self, text: &str, tile: Tile) -> String {
+        match self.color(tile) {
+            "" => text.to_string(),
+            color => format!("{color}{text}{RESET}"),
+        }
+    }
+
+    /// A histogram bar `len` cells long followed by its `label`.
+    pub fn bar(&self, len: usize, label: &str, highlight: bool) -> String {
+        let tile = if highlight {
+            Tile::Correct
+        } else {
+            Tile::Unused
+        };
+        match self {
+            Theme::Mono => format!(
+                "{} {label}{}",
+                "#".repeat(len),
+                if highlight { " <" } else { "" }
+            ),
+            _ => format!(
+                "{}{}",
+                self.paint(&" ".repeat(len), tile),
+                self.paint(label, tile)
+            ),
+        }
+    }
+
+    // gap between keys on the keyboard
+    pub fn key_gap(&self) -> &'static str {
+        match self {
+            Theme::Mono => "",
+            _ => " ",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::theme::{Theme, Tile};
+
+    #[test]
+    fn mono_has_no_escape_codes() {
+        let mono = Theme::Mono;
+        assert_eq!(mono.tile('A', Tile::Correct), "[A]");
+        assert_eq!(mono.tile('A', Tile::Present), "(A)");
+        assert_eq!(mono.tile('A', Tile::Plain), " A ");
+        assert_eq!(mono.bar(3, "2", true), "### 2 <");
+        for theme in Theme::ALL {
+            assert_eq!(theme.to_string().parse::<Theme>(), Ok(theme));
+        }
+        assert_ne!(
+            Theme::Classic.tile('A', Tile::Correct),
+            Theme::Contrast.tile('A', Tile::Correct)
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    panic,
+    sync::{mpsc::Sender, Once},
+    thread,
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute, queue,
+    terminal::{self, ClearType},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    Enter,
+    Hint,
+    Quit,
+    // a whole line typed in when stdin isn't a terminal, submitted in one go
+    Line(String),
+}
+
+/// Raw mode for as long as this lives, so every keystroke arrives on its own and nothing gets
+/// echoed. The terminal is put back when it's dropped, or if anything panics first.
+pub struct Terminal {
+    raw: bool,
+    out: io::Stdout,
+}
+
+impl Terminal {
+    /// When stdin isn't a terminal (piped in, or a test) input is read a line at a time instead.
+    pub fn new() -> io::Result<Self> {
+        let raw = io::stdin().is_terminal();
+        if raw {
+            restore_on_panic();
+            terminal::enable_raw_mode()?;
+            execute!(io::stdout(), cursor::Hide)?;
+        }
+        Ok(Self {
+            raw,
+            out: io::stdout(),
+        })
+    }
+
+    pub fn is_raw(&self) -> bool {
+        self.raw
+    }
+
+    pub fn draw(&mut self, frame: &str) -> io::Result<()> {
+        queue!(
+            self.out,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(ClearType::All)
+        )?;
+        if self.raw {
+            // raw mode doesn't turn \n into \r\n for us
+            self.out.write_all(frame.replace('\n', "\r\n").as_bytes())?;
+        } else {
+            self.out.write_all(frame.as_bytes())?;
+        }
+        self.out.flush()
+    }
+
+    pub fn read_key(&mut self) -> io::Result<Key> {
+        read_key(self.raw)
+    }
+}
+
+/// The next key pressed, or the next line typed in if `raw` is false. For reading keys on another
+/// thread while a [`Terminal`] draws on this one.
+pub fn read_key(raw: bool) -> io::Result<Key> {
+    if !raw {
+        return read_line();
+    }
+    loop {
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Release {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c' | 'd') if ctrl => return Ok(Key::Quit),
+            KeyCode::Char('?') | KeyCode::Tab => return Ok(Key::Hint),
+            KeyCode::Char(c) if c.is_alphabetic() => {
+                return Ok(Key::Char(c.to_uppercase().next().unwrap_or(c)))
+            }
+            KeyCode::Backspace => return Ok(Key::Backspace),
+            KeyCode::Enter => return Ok(Key::Enter),
+            KeyCode::Esc => return Ok(Key::Quit),
+            _ => {}
+        }
+    }
+}
+
+/// Reads keys on another thread and sends each one down `tx`, wrapped by `wrap`, so they can be
+/// waited on alongside anything else. Stops after a quit or once nobody is listening.
+pub fn send_keys<T: Send + 'static>(raw: bool, tx: Sender<T>, wrap: fn(Key) -> T) {
+    thread::spawn(move || loop {
+        let key = read_key(raw).unwrap_or(Key::Quit);
+        let quit = key == Key::Quit;
+        if tx.send(wrap(key)).is_err() || quit {
+            break;
+        }
+    });
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        if self.raw {
+            restore();
+            println!();
+        }
+    }
+}
+
+fn restore() {
+    let _ = execute!(io::stdout(), cursor::Show);
+    let _ = terminal::disable_raw_mode();
+}
+
+fn restore_on_panic() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            hook(info);
+        }));
+    });
+}
+
+fn read_line() -> io::Result<Key> {
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        // stdin closed, nobody is left to play
+        return Ok(Key::Quit);
+    }
+    let line = line.trim();
+    Ok(match line {
+        "" => Key::Enter,
+        "?" | "hint" => Key::Hint,
+        _ => Key::Line(line.to_uppercase()),
+    })
+}